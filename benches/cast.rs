@@ -0,0 +1,70 @@
+//! Measures the cost of [`DynPtr::cast`] itself, for both matching and
+//! non-matching trait object types, and compares it with a cache of the most
+//! recent cast result.
+//!
+//! Run with `cargo +nightly bench`.
+
+#![feature(test)]
+
+extern crate test;
+
+use any_dyn::DynPtr;
+use core::{fmt::Debug, ptr::NonNull};
+use test::{Bencher, black_box};
+
+trait Unrelated {}
+
+#[bench]
+fn cast_match(b: &mut Bencher) {
+    let value = 5_u32;
+    let ptr = DynPtr::new(NonNull::from(&value as &dyn Debug));
+    b.iter(|| black_box(&ptr).cast::<dyn Debug>());
+}
+
+#[bench]
+fn cast_mismatch(b: &mut Bencher) {
+    let value = 5_u32;
+    let ptr = DynPtr::new(NonNull::from(&value as &dyn Debug));
+    b.iter(|| black_box(&ptr).cast::<dyn Unrelated>());
+}
+
+/// A cache of the most recent cast, keyed by object address and trait object
+/// type, as might be used to short-circuit repeated casts of the same object.
+///
+/// This exists only to measure whether such a cache could pay for itself. It
+/// can't, because checking the cache costs at least as much as the
+/// [`DynPtr::cast`] it would replace.
+struct LastCast {
+    last: Option<(DynPtr, NonNull<dyn Debug>)>,
+}
+
+impl LastCast {
+    fn cast(&mut self, ptr: &DynPtr) -> Option<NonNull<dyn Debug>> {
+        if let Some((last_ptr, result)) = self.last
+            && last_ptr.ptr_eq(ptr)
+        {
+            return Some(result);
+        }
+        let result = ptr.cast::<dyn Debug>()?;
+        self.last = Some((*ptr, result));
+        Some(result)
+    }
+}
+
+#[bench]
+fn cast_memoized_hit(b: &mut Bencher) {
+    let value = 5_u32;
+    let ptr = DynPtr::new(NonNull::from(&value as &dyn Debug));
+    let mut memo = LastCast { last: None };
+    b.iter(|| memo.cast(black_box(&ptr)));
+}
+
+/// The interned form adds one pointer indirection to reach the shared
+/// metadata, but is less than half the size of a [`DynPtr`].
+#[cfg(feature = "std")]
+#[bench]
+fn cast_interned_match(b: &mut Bencher) {
+    let value = 5_u32;
+    let ptr = any_dyn::InternedDynPtr::new(DynPtr::new(NonNull::from(&value as &dyn Debug)));
+    b.iter(|| black_box(&ptr).cast::<dyn Debug>());
+}
@@ -0,0 +1,67 @@
+//! Compares the cost of casting through a [`match_dyn_type_id`] chain with
+//! the cost of casting through a [`TraitTable`], for varying numbers of
+//! supported traits.
+//!
+//! Run with `cargo +nightly bench --features alloc`.
+
+#![feature(test)]
+
+extern crate test;
+
+use any_dyn::{
+    Dyn, DynTypeId,
+    traitcast::{AsTraitObject, TraitTable, cast_trait_object, match_dyn_type_id},
+};
+use test::{Bencher, black_box};
+
+// Each distinct value of N gives a distinct trait object type, which lets us
+// generate arbitrarily-many traits without naming each one separately.
+trait Numbered<const N: usize> {
+    fn number(&self) -> usize {
+        N
+    }
+}
+
+struct Implementer;
+
+impl<const N: usize> Numbered<N> for Implementer {}
+
+macro_rules! dispatch_benches {
+    ($chain_name:ident, $table_name:ident, $chain_ty:ident, $last:literal; $($n:literal),+) => {
+        struct $chain_ty(Implementer);
+
+        impl AsTraitObject for $chain_ty {
+            fn as_trait_object<'a>(&'a self, type_id: DynTypeId) -> Option<Dyn<'a>> {
+                match_dyn_type_id!(&self.0, type_id => $(Numbered<$n>),+)
+            }
+        }
+
+        #[bench]
+        fn $chain_name(b: &mut Bencher) {
+            let obj = $chain_ty(Implementer);
+            b.iter(|| {
+                let obj: &dyn AsTraitObject = black_box(&obj);
+                // The last trait in the list is the worst case for the chain.
+                cast_trait_object::<dyn Numbered<$last>>(obj).map(|o| o.number())
+            });
+        }
+
+        #[bench]
+        fn $table_name(b: &mut Bencher) {
+            let obj = Implementer;
+            let mut table = TraitTable::new();
+            $(
+                table.insert(Dyn::new(&obj as &dyn Numbered<$n>));
+            )+
+            b.iter(|| {
+                let table: &dyn AsTraitObject = black_box(&table);
+                cast_trait_object::<dyn Numbered<$last>>(table).map(|o| o.number())
+            });
+        }
+    };
+}
+
+dispatch_benches!(chain_002, table_002, Chain2, 1; 0, 1);
+dispatch_benches!(chain_008, table_008, Chain8, 7; 0, 1, 2, 3, 4, 5, 6, 7);
+dispatch_benches!(chain_032, table_032, Chain32, 31; 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31);
+dispatch_benches!(chain_128, table_128, Chain128, 127; 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31, 32, 33, 34, 35, 36, 37, 38, 39, 40, 41, 42, 43, 44, 45, 46, 47, 48, 49, 50, 51, 52, 53, 54, 55, 56, 57, 58, 59, 60, 61, 62, 63, 64, 65, 66, 67, 68, 69, 70, 71, 72, 73, 74, 75, 76, 77, 78, 79, 80, 81, 82, 83, 84, 85, 86, 87, 88, 89, 90, 91, 92, 93, 94, 95, 96, 97, 98, 99, 100, 101, 102, 103, 104, 105, 106, 107, 108, 109, 110, 111, 112, 113, 114, 115, 116, 117, 118, 119, 120, 121, 122, 123, 124, 125, 126, 127);
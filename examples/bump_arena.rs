@@ -0,0 +1,104 @@
+//! A minimal sketch of allocating trait objects in a bump arena, and then
+//! recovering references to them that live as long as the arena.
+//!
+//! Run with `cargo +nightly run --example bump_arena`.
+
+use any_dyn::{ArenaDyn, DynArena};
+use core::{
+    alloc::Layout,
+    cell::{Cell, UnsafeCell},
+    mem::MaybeUninit,
+    ptr::NonNull,
+};
+
+const CAPACITY: usize = 1024;
+
+/// A bump allocator over a fixed-size buffer. Allocation just advances an
+/// offset, and nothing is freed until the whole arena is dropped.
+struct Bump {
+    buf: UnsafeCell<[MaybeUninit<u8>; CAPACITY]>,
+    used: Cell<usize>,
+}
+
+impl Bump {
+    fn new() -> Self {
+        Self {
+            buf: UnsafeCell::new([MaybeUninit::uninit(); CAPACITY]),
+            used: Cell::new(0),
+        }
+    }
+}
+
+// Safety: Each allocation is a distinct, suitably-aligned part of `buf`,
+// and the offset only ever increases, so no part of the buffer is handed
+// out twice while the arena is borrowed.
+unsafe impl DynArena for Bump {
+    fn alloc_layout(&self, layout: Layout) -> NonNull<u8> {
+        let base = self.buf.get().cast::<u8>();
+        let start = (base as usize + self.used.get()).next_multiple_of(layout.align());
+        let end = start + layout.size();
+        assert!(end <= base as usize + CAPACITY, "arena is full");
+        self.used.set(end - base as usize);
+        NonNull::new(base.with_addr(start)).unwrap()
+    }
+}
+
+trait Shape {
+    fn area(&self) -> f64;
+}
+
+trait Named {
+    fn name(&self) -> &str;
+}
+
+struct Square(f64);
+
+impl Shape for Square {
+    fn area(&self) -> f64 {
+        self.0 * self.0
+    }
+}
+
+struct Circle(f64);
+
+impl Shape for Circle {
+    fn area(&self) -> f64 {
+        core::f64::consts::PI * self.0 * self.0
+    }
+}
+
+struct Label(&'static str);
+
+impl Named for Label {
+    fn name(&self) -> &str {
+        self.0
+    }
+}
+
+/// Builds a mixed collection of objects in the arena. The results borrow
+/// from the arena, not from anything local to this function.
+fn populate(arena: &Bump) -> [ArenaDyn<'_>; 4] {
+    [
+        ArenaDyn::alloc(arena, Square(2.0), |v| v as &dyn Shape),
+        ArenaDyn::alloc(arena, Label("origin"), |v| v as &dyn Named),
+        ArenaDyn::alloc(arena, Circle(1.0), |v| v as &dyn Shape),
+        ArenaDyn::alloc(arena, Label("unit circle"), |v| v as &dyn Named),
+    ]
+}
+
+fn main() {
+    let arena = Bump::new();
+    let objs = populate(&arena);
+    println!("{} bytes of the arena in use", arena.used.get());
+
+    let total_area: f64 = objs
+        .iter()
+        .filter_map(|obj| obj.cast::<dyn Shape>())
+        .map(|shape| shape.area())
+        .sum();
+    println!("total area: {total_area:.3}");
+
+    for name in objs.iter().filter_map(|obj| obj.cast::<dyn Named>()) {
+        println!("label: {}", name.name());
+    }
+}
@@ -0,0 +1,63 @@
+//! A minimal sketch of a global service locator, where each service is a
+//! lazily-created singleton exposed only through the traits it implements.
+//!
+//! Run with `cargo +nightly run --example global_singleton --features std`.
+
+use any_dyn::{LazyStaticDyn, StaticDyn};
+
+trait Clock: Send + Sync {
+    fn now(&self) -> u64;
+}
+
+trait Logger: Send + Sync {
+    fn log(&self, msg: &str);
+}
+
+struct FixedClock(u64);
+
+impl Clock for FixedClock {
+    fn now(&self) -> u64 {
+        self.0
+    }
+}
+
+struct StdoutLogger {
+    prefix: String,
+}
+
+impl Logger for StdoutLogger {
+    fn log(&self, msg: &str) {
+        println!("{}{msg}", self.prefix);
+    }
+}
+
+// Neither service can be constructed in a const context, so each is created
+// and leaked on first use instead.
+static CLOCK: LazyStaticDyn = LazyStaticDyn::new(|| {
+    println!("(initializing clock)");
+    StaticDyn::new(Box::leak(Box::new(FixedClock(1_700_000_000))) as &dyn Clock)
+});
+static LOGGER: LazyStaticDyn = LazyStaticDyn::new(|| {
+    println!("(initializing logger)");
+    let prefix = format!("[pid {}] ", std::process::id());
+    StaticDyn::new(Box::leak(Box::new(StdoutLogger { prefix })) as &dyn Logger)
+});
+
+fn main() {
+    let services = [&CLOCK, &LOGGER];
+
+    // Find each service by the trait it offers, without knowing in advance
+    // which static holds it.
+    let logger = services
+        .iter()
+        .find_map(|s| s.cast::<dyn Logger>())
+        .unwrap();
+    logger.log("started");
+
+    let clock = services.iter().find_map(|s| s.cast::<dyn Clock>()).unwrap();
+    logger.log(&format!("the time is {}", clock.now()));
+
+    // Later uses don't run the initialization functions again.
+    let again = LOGGER.cast::<dyn Logger>().unwrap();
+    again.log("finished");
+}
@@ -0,0 +1,53 @@
+//! A minimal sketch of an "open method" for a scene graph, where the
+//! renderer decides how to draw each node based on the trait the node was
+//! registered as, and new kinds of node can be supported just by registering
+//! another handler.
+//!
+//! Run with `cargo +nightly run --example open_method --features alloc`.
+
+use any_dyn::{Dyn, traitcast::DynDispatcher};
+
+trait Shape {
+    fn area(&self) -> f64;
+}
+
+trait Label {
+    fn text(&self) -> &str;
+}
+
+struct Square(f64);
+
+impl Shape for Square {
+    fn area(&self) -> f64 {
+        self.0 * self.0
+    }
+}
+
+struct Caption(&'static str);
+
+impl Label for Caption {
+    fn text(&self) -> &str {
+        self.0
+    }
+}
+
+fn main() {
+    let mut render = DynDispatcher::new();
+    render.register::<dyn Shape>(|s| format!("shape with area {:.1}", s.area()));
+    render.register::<dyn Label>(|l| format!("label reading {:?}", l.text()));
+
+    let square = Square(1.5);
+    let caption = Caption("hello");
+    let nodes = [
+        Dyn::new(&square as &dyn Shape),
+        Dyn::new(&caption as &dyn Label),
+        Dyn::new(&caption as &dyn std::any::Any),
+    ];
+
+    for node in nodes {
+        match render.dispatch(node) {
+            Some(drawn) => println!("drew {drawn}"),
+            None => println!("skipped a node with no renderer"),
+        }
+    }
+}
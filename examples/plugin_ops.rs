@@ -0,0 +1,48 @@
+//! A minimal sketch of a plugin interface where each plugin registers named
+//! operations of whatever signature it likes, and the host calls them by
+//! name with the signature it expects.
+//!
+//! Run with `cargo +nightly run --example plugin_ops --features alloc`.
+
+use any_dyn::{Dyn, traitcast::OpTable};
+
+/// A plugin offering arithmetic on integers.
+fn register_math(ops: &mut OpTable<'_>) {
+    fn triple(v: u32) -> u32 {
+        v * 3
+    }
+    ops.insert("triple", Dyn::new(&triple as &dyn Fn(u32) -> u32));
+}
+
+/// A plugin offering text formatting.
+fn register_text(ops: &mut OpTable<'_>) {
+    fn shout(s: &'static str) -> String {
+        s.to_uppercase()
+    }
+    ops.insert("shout", Dyn::new(&shout as &dyn Fn(&'static str) -> String));
+}
+
+fn main() {
+    let mut ops = OpTable::new();
+    register_math(&mut ops);
+    register_text(&mut ops);
+
+    println!(
+        "available operations: {:?}",
+        ops.names().collect::<Vec<_>>()
+    );
+
+    let scaled = ops.invoke::<u32, u32>("triple", 14);
+    println!("triple(14) = {scaled:?}");
+    assert_eq!(scaled, Some(42));
+
+    let shouted = ops.invoke::<&'static str, String>("shout", "hello");
+    println!("shout(\"hello\") = {shouted:?}");
+    assert_eq!(shouted.as_deref(), Some("HELLO"));
+
+    // Calling an operation with a signature other than the one it was
+    // registered with is detected, rather than being undefined behavior.
+    let mismatched = ops.invoke::<&'static str, String>("triple", "oops");
+    println!("triple(\"oops\") = {mismatched:?}");
+    assert_eq!(mismatched, None);
+}
@@ -0,0 +1,112 @@
+//! A minimal sketch of an ECS-style "world" of heterogeneous resources, where
+//! systems find the resources they need by the traits those resources
+//! implement rather than by their concrete types.
+//!
+//! Run with `cargo +nightly run --example world --features alloc`.
+
+use any_dyn::{
+    Dyn, DynTypeId,
+    traitcast::{AsTraitObject, cast_then_downcast, cast_trait_object, match_dyn_type_id},
+};
+use std::any::Any;
+
+/// Resources that need to be advanced on each tick of the simulation.
+trait Tick {
+    fn tick(&self, dt: f32);
+}
+
+/// Resources that can contribute a line to a status report.
+trait Report {
+    fn report(&self) -> String;
+}
+
+struct Clock {
+    elapsed: std::cell::Cell<f32>,
+}
+
+impl Tick for Clock {
+    fn tick(&self, dt: f32) {
+        self.elapsed.set(self.elapsed.get() + dt);
+    }
+}
+
+impl Report for Clock {
+    fn report(&self) -> String {
+        format!("clock: {:.1}s elapsed", self.elapsed.get())
+    }
+}
+
+impl AsTraitObject for Clock {
+    fn as_trait_object<'a>(&'a self, type_id: DynTypeId) -> Option<Dyn<'a>> {
+        match_dyn_type_id!(self, type_id => Tick, Report, Any)
+    }
+}
+
+struct Score(u32);
+
+impl Report for Score {
+    fn report(&self) -> String {
+        format!("score: {}", self.0)
+    }
+}
+
+impl AsTraitObject for Score {
+    fn as_trait_object<'a>(&'a self, type_id: DynTypeId) -> Option<Dyn<'a>> {
+        match_dyn_type_id!(self, type_id => Report, Any)
+    }
+}
+
+/// Owns all of the resources, without knowing any of their concrete types.
+#[derive(Default)]
+struct World {
+    resources: Vec<Box<dyn AsTraitObject>>,
+}
+
+impl World {
+    fn insert(&mut self, resource: impl AsTraitObject + 'static) {
+        self.resources.push(Box::new(resource));
+    }
+
+    /// Returns all of the resources that offer the trait object type `Dyn`.
+    fn query<Dyn: any_dyn::TraitObject + ?Sized + 'static>(&self) -> impl Iterator<Item = &Dyn> {
+        self.resources
+            .iter()
+            .filter_map(|resource| cast_trait_object::<Dyn>(resource))
+    }
+
+    /// Returns the first resource of the concrete type `T`, for resources
+    /// that offer `dyn Any`.
+    fn resource<T: Any>(&self) -> Option<&T> {
+        self.resources
+            .iter()
+            .find_map(|resource| cast_then_downcast::<T>(resource))
+    }
+}
+
+fn tick_system(world: &World) {
+    for resource in world.query::<dyn Tick>() {
+        resource.tick(0.5);
+    }
+}
+
+fn report_system(world: &World) {
+    for resource in world.query::<dyn Report>() {
+        println!("{}", resource.report());
+    }
+}
+
+fn main() {
+    let mut world = World::default();
+    world.insert(Clock {
+        elapsed: Default::default(),
+    });
+    world.insert(Score(42));
+
+    for _ in 0..3 {
+        tick_system(&world);
+    }
+    report_system(&world);
+
+    let score = world.resource::<Score>().unwrap();
+    println!("the score resource holds {}", score.0);
+}
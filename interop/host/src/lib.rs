@@ -0,0 +1,3 @@
+//! The host side of the cross-crate interoperability tests, which lives in
+//! `tests/cross_crate.rs` so that it is a separate crate from both this
+//! library and the plugin.
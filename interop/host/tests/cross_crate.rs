@@ -0,0 +1,43 @@
+//! Verifies that trait objects erased in one crate can be cast in another,
+//! when both crates use the same trait from a third crate.
+
+use any_dyn::{Dyn, DynTypeId, traitcast::cast_trait_object};
+use shared_trait::SharedTrait;
+
+#[test]
+fn dyn_type_id_agrees_across_crates() {
+    assert_eq!(
+        plugin::shared_trait_id(),
+        DynTypeId::of::<dyn SharedTrait>()
+    );
+}
+
+#[test]
+fn cast_value_erased_in_other_crate() {
+    let erased = plugin::erased_widget();
+    assert_eq!(erased.dyn_type_id(), DynTypeId::of::<dyn SharedTrait>());
+    let widget = erased.cast::<dyn SharedTrait>().unwrap();
+    assert_eq!(widget.describe(), "widget from plugin");
+}
+
+#[test]
+fn cast_through_other_crates_as_trait_object() {
+    let widget = plugin::Widget { name: "local" };
+    let shared = cast_trait_object::<dyn SharedTrait>(&widget).unwrap();
+    assert_eq!(shared.describe(), "widget local");
+}
+
+#[test]
+fn other_crate_casts_value_erased_here() {
+    struct Local;
+
+    impl SharedTrait for Local {
+        fn describe(&self) -> String {
+            "local".to_string()
+        }
+    }
+
+    let erased = Dyn::new(&Local as &dyn SharedTrait);
+    assert_eq!(erased.dyn_type_id(), plugin::shared_trait_id());
+    assert_eq!(plugin::describe(erased).as_deref(), Some("local"));
+}
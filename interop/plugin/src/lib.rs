@@ -0,0 +1,42 @@
+//! Erases trait objects of [`SharedTrait`] for a separate crate to recover,
+//! and recovers trait objects that a separate crate erased.
+
+use any_dyn::{Dyn, DynTypeId, traitcast::AsTraitObject};
+use shared_trait::SharedTrait;
+
+/// An implementation of [`SharedTrait`] that the host crate doesn't know
+/// about.
+pub struct Widget {
+    pub name: &'static str,
+}
+
+impl SharedTrait for Widget {
+    fn describe(&self) -> String {
+        format!("widget {}", self.name)
+    }
+}
+
+impl AsTraitObject for Widget {
+    fn as_trait_object<'a>(&'a self, type_id: DynTypeId) -> Option<Dyn<'a>> {
+        any_dyn::traitcast::match_dyn_type_id!(self, type_id => SharedTrait)
+    }
+}
+
+/// Returns a widget erased as `dyn SharedTrait` within this crate.
+pub fn erased_widget() -> Dyn<'static> {
+    static WIDGET: Widget = Widget {
+        name: "from plugin",
+    };
+    Dyn::new(&WIDGET as &dyn SharedTrait)
+}
+
+/// Returns the identity of `dyn SharedTrait` as seen from this crate.
+pub fn shared_trait_id() -> DynTypeId {
+    DynTypeId::of::<dyn SharedTrait>()
+}
+
+/// Describes `obj` if it was erased as `dyn SharedTrait`, regardless of which
+/// crate erased it.
+pub fn describe(obj: Dyn<'_>) -> Option<String> {
+    obj.cast::<dyn SharedTrait>().map(SharedTrait::describe)
+}
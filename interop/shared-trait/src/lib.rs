@@ -0,0 +1,10 @@
+//! A trait shared between `any-dyn-interop-plugin` and
+//! `any-dyn-interop-host`, which each see it only through this crate.
+//!
+//! This is part of a test verifying that trait objects erased by one crate
+//! can be cast back by another, as long as both name the same trait.
+
+/// A trait the plugin implements and the host calls.
+pub trait SharedTrait {
+    fn describe(&self) -> String;
+}
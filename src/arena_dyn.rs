@@ -0,0 +1,131 @@
+use core::{alloc::Layout, ptr::NonNull};
+
+use crate::{Dyn, DynPtr, DynTypeId, TraitObject};
+
+/// An arena allocator that [`ArenaDyn`] can allocate trait objects in.
+///
+/// Objects allocated in an arena live until the arena itself is dropped, so
+/// a shared borrow of the arena is a witness that they are all still valid.
+/// Implementing this trait for an arena type lets [`ArenaDyn::alloc`] rely
+/// on that, so that code using the arena can allocate trait objects and
+/// recover references to them without any `unsafe` of its own.
+///
+/// # Safety
+///
+/// [`DynArena::alloc_layout`] must return a pointer to a block of memory
+/// that fits `layout`, and that is not used for anything else, nor freed,
+/// for as long as the arena is borrowed. In particular the arena must not
+/// allow the memory to be reused through a shared reference, such as by a
+/// `reset` method that takes `&self`.
+pub unsafe trait DynArena {
+    /// Allocates a block of uninitialized memory that fits `layout`.
+    ///
+    /// Implementations that cannot satisfy the request must panic or abort,
+    /// rather than return a pointer to a smaller block.
+    fn alloc_layout(&self, layout: Layout) -> NonNull<u8>;
+}
+
+/// A [`Dyn`] for an object allocated in a [`DynArena`], which remains valid
+/// for as long as the arena is borrowed.
+///
+/// This packages the pattern of allocating objects in an arena and then
+/// recovering trait object references to them with the arena's lifetime,
+/// using [`ArenaDyn::alloc`] in place of the unsafe [`Dyn::from_raw_in`].
+///
+/// ```
+/// # use any_dyn::{ArenaDyn, DynArena};
+/// # use core::{alloc::Layout, cell::{Cell, UnsafeCell}, fmt::Display, mem::MaybeUninit, ptr::NonNull};
+/// /// A bump allocator over a fixed-size buffer, which never frees anything.
+/// struct Bump {
+///     buf: UnsafeCell<[MaybeUninit<u8>; 256]>,
+///     used: Cell<usize>,
+/// }
+///
+/// // Safety: Each allocation is a distinct, suitably-aligned part of `buf`,
+/// // and nothing can reuse it until `Bump` is dropped or mutably borrowed.
+/// unsafe impl DynArena for Bump {
+///     fn alloc_layout(&self, layout: Layout) -> NonNull<u8> {
+///         let base = self.buf.get().cast::<u8>();
+///         let start = (base as usize + self.used.get()).next_multiple_of(layout.align());
+///         let end = start + layout.size();
+///         assert!(end <= base as usize + 256, "arena is full");
+///         self.used.set(end - base as usize);
+///         NonNull::new(base.with_addr(start)).unwrap()
+///     }
+/// }
+///
+/// let arena = Bump { buf: UnsafeCell::new([MaybeUninit::uninit(); 256]), used: Cell::new(0) };
+/// let objs = [
+///     ArenaDyn::alloc(&arena, 1, |v| v as &dyn Display),
+///     ArenaDyn::alloc(&arena, "two", |v| v as &dyn Display),
+/// ];
+/// let strs = objs.map(|obj| obj.cast::<dyn Display>().unwrap().to_string());
+/// assert_eq!(strs, ["1", "two"]);
+/// ```
+///
+/// Values moved into the arena are never dropped, in the same way as
+/// values passed to [`core::mem::forget`].
+#[derive(Debug, Clone, Copy)]
+pub struct ArenaDyn<'arena> {
+    inner: Dyn<'arena>,
+}
+
+impl<'arena> ArenaDyn<'arena> {
+    /// Moves `value` into `arena` and erases it as the trait object that
+    /// `erase` returns for it.
+    ///
+    /// `erase` is normally just a coercion, such as `|v| v as &dyn Trait`.
+    #[inline]
+    pub fn alloc<A, T, Dyn>(
+        arena: &'arena A,
+        value: T,
+        erase: impl FnOnce(&'arena T) -> &'arena Dyn,
+    ) -> Self
+    where
+        A: DynArena + ?Sized,
+        T: 'arena,
+        Dyn: TraitObject + ?Sized + 'static,
+    {
+        let ptr = arena.alloc_layout(Layout::new::<T>()).cast::<T>();
+        // Safety: DynArena promises that the block fits a `T`, and that
+        // nothing else uses it while the arena is borrowed for `'arena`.
+        let obj = unsafe {
+            ptr.write(value);
+            ptr.as_ref()
+        };
+        Self {
+            inner: crate::Dyn::new(erase(obj)),
+        }
+    }
+
+    /// Returns a reference to a trait object of type `Dyn` if and only if
+    /// this value was allocated as a trait object of the same type.
+    ///
+    /// The result borrows from the arena rather than from this value.
+    #[inline]
+    pub fn cast<Dyn: TraitObject + ?Sized + 'static>(self) -> Option<&'arena Dyn> {
+        self.inner.cast::<Dyn>()
+    }
+
+    /// Returns the underlying [`Dyn`].
+    #[inline]
+    pub const fn as_dyn(self) -> Dyn<'arena> {
+        self.inner
+    }
+
+    /// Returns the underlying [`DynPtr`], for storing without a lifetime.
+    ///
+    /// Use [`Dyn::from_raw_in`] with the same arena to recover a [`Dyn`]
+    /// from the result.
+    #[inline]
+    pub const fn as_ptr(self) -> DynPtr {
+        self.inner.as_ptr()
+    }
+
+    /// Returns the [`DynTypeId`] of the trait object type this value was
+    /// allocated as.
+    #[inline]
+    pub const fn dyn_type_id(self) -> DynTypeId {
+        self.inner.dyn_type_id()
+    }
+}
@@ -0,0 +1,204 @@
+use alloc::boxed::Box;
+
+use crate::DynPtr;
+
+/// A heap-allocated [`DynPtr`], represented as a single thin pointer.
+///
+/// [`DynPtr`] is several words in size, because it carries the trait object
+/// metadata and trait object type alongside the address of the object, and
+/// so it cannot be updated atomically. [`BoxedDynPtr`] moves all of that into
+/// a separate heap allocation, so that the handle itself is just one pointer
+/// that can be stored in an [`AtomicPtr`](core::sync::atomic::AtomicPtr),
+/// either directly using [`BoxedDynPtr::into_raw`] and
+/// [`BoxedDynPtr::from_raw`] or through [`AtomicDynPtr`].
+///
+/// The cost of this is an allocation for each handle, and an extra pointer
+/// indirection each time the [`DynPtr`] is read back out.
+///
+/// Unlike [`DynPtr`], [`BoxedDynPtr`] implements [`Send`] and [`Sync`] so
+/// that it can be handed between threads. It offers no access to the object
+/// beyond a copy of the raw pointer, so the caller remains responsible for
+/// the thread-safety of any use of the object it refers to.
+///
+/// This is available only when the `alloc` feature is enabled.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BoxedDynPtr {
+    ptr: Box<DynPtr>,
+}
+
+// Safety: A BoxedDynPtr only owns its heap allocation, which contains plain
+// data. The pointer it contains can only be dereferenced using unsafe code,
+// which must itself account for which threads the object is used from.
+unsafe impl Send for BoxedDynPtr {}
+unsafe impl Sync for BoxedDynPtr {}
+
+impl BoxedDynPtr {
+    /// Moves the given pointer into a new heap allocation.
+    #[inline]
+    pub fn new(ptr: DynPtr) -> Self {
+        Self { ptr: Box::new(ptr) }
+    }
+
+    /// Returns a copy of the boxed pointer.
+    #[inline]
+    pub fn get(&self) -> DynPtr {
+        *self.ptr
+    }
+
+    /// Consumes the box, returning a thin pointer to the heap allocation
+    /// containing the [`DynPtr`].
+    ///
+    /// The allocation is leaked unless the result is later passed to
+    /// [`BoxedDynPtr::from_raw`].
+    #[inline]
+    pub fn into_raw(self) -> *mut DynPtr {
+        Box::into_raw(self.ptr)
+    }
+
+    /// Reconstructs a [`BoxedDynPtr`] from the result of an earlier call to
+    /// [`BoxedDynPtr::into_raw`].
+    ///
+    /// # Safety
+    ///
+    /// `raw` must have been returned by [`BoxedDynPtr::into_raw`], and must
+    /// not be passed to this function more than once.
+    #[inline]
+    pub unsafe fn from_raw(raw: *mut DynPtr) -> Self {
+        Self {
+            // Safety: The caller must ensure the pointer came from
+            // Box::into_raw and is not reused.
+            ptr: unsafe { Box::from_raw(raw) },
+        }
+    }
+}
+
+impl From<DynPtr> for BoxedDynPtr {
+    #[inline]
+    fn from(ptr: DynPtr) -> Self {
+        Self::new(ptr)
+    }
+}
+
+/// An optional [`BoxedDynPtr`] that can be replaced atomically, for use in
+/// lock-free data structures.
+///
+/// Each operation transfers ownership of a whole [`BoxedDynPtr`] into or out
+/// of the slot, so each heap allocation is only ever accessed by the thread
+/// that currently owns it. There is intentionally no way to read the current
+/// value without taking it out, because another thread could free it at any
+/// time after it has been read. Structures that need to share the current
+/// value between threads must use [`AtomicDynPtr::as_atomic_ptr`] along with
+/// their own memory reclamation scheme, such as epochs or hazard pointers.
+///
+/// ```
+/// # use any_dyn::{AtomicDynPtr, BoxedDynPtr, DynPtr};
+/// # use core::{fmt::Debug, ptr::NonNull, sync::atomic::Ordering};
+/// static LATEST: AtomicDynPtr = AtomicDynPtr::empty();
+///
+/// static VALUE: u32 = 5;
+/// let ptr = DynPtr::new(NonNull::from(&VALUE as &(dyn Debug + Sync)));
+/// assert!(LATEST.swap(Some(ptr.into()), Ordering::AcqRel).is_none());
+///
+/// let taken = LATEST.take(Ordering::AcqRel).unwrap();
+/// assert!(taken.get().ptr_eq(&ptr));
+/// assert!(LATEST.take(Ordering::AcqRel).is_none());
+/// ```
+///
+/// This is available only when the `alloc` feature is enabled, and only on
+/// targets that support atomic pointers.
+#[cfg(target_has_atomic = "ptr")]
+#[derive(Debug)]
+pub struct AtomicDynPtr {
+    raw: core::sync::atomic::AtomicPtr<DynPtr>,
+}
+
+#[cfg(target_has_atomic = "ptr")]
+impl AtomicDynPtr {
+    /// Creates a slot containing the given pointer, if any.
+    #[inline]
+    pub fn new(ptr: Option<BoxedDynPtr>) -> Self {
+        Self {
+            raw: core::sync::atomic::AtomicPtr::new(Self::into_raw(ptr)),
+        }
+    }
+
+    /// Creates an empty slot.
+    #[inline]
+    pub const fn empty() -> Self {
+        Self {
+            raw: core::sync::atomic::AtomicPtr::new(core::ptr::null_mut()),
+        }
+    }
+
+    /// Stores `ptr` in the slot, returning the pointer it replaced, if any.
+    #[inline]
+    pub fn swap(
+        &self,
+        ptr: Option<BoxedDynPtr>,
+        order: core::sync::atomic::Ordering,
+    ) -> Option<BoxedDynPtr> {
+        let old = self.raw.swap(Self::into_raw(ptr), order);
+        // Safety: Every non-null pointer stored in the slot came from
+        // BoxedDynPtr::into_raw, and the swap transferred ownership to us.
+        unsafe { Self::from_raw(old) }
+    }
+
+    /// Removes and returns the pointer in the slot, if any, leaving it empty.
+    #[inline]
+    pub fn take(&self, order: core::sync::atomic::Ordering) -> Option<BoxedDynPtr> {
+        self.swap(None, order)
+    }
+
+    /// Returns the underlying atomic pointer, for use by data structures
+    /// that implement their own memory reclamation.
+    ///
+    /// # Safety
+    ///
+    /// The slot owns whatever non-null pointer it contains, and frees it when
+    /// it is taken, swapped out or dropped. Any non-null pointer stored
+    /// through the result must therefore have been returned by
+    /// [`BoxedDynPtr::into_raw`], and storing it transfers ownership of it to
+    /// the slot. Any pointer loaded or swapped out through the result must not
+    /// be passed to [`BoxedDynPtr::from_raw`] unless it has also been removed
+    /// from the slot, and must not be dereferenced after another thread could
+    /// have removed and freed it.
+    #[inline]
+    pub unsafe fn as_atomic_ptr(&self) -> &core::sync::atomic::AtomicPtr<DynPtr> {
+        &self.raw
+    }
+
+    /// Consumes the slot, returning the pointer it contained, if any.
+    #[inline]
+    pub fn into_inner(self) -> Option<BoxedDynPtr> {
+        self.take(core::sync::atomic::Ordering::Relaxed)
+    }
+
+    fn into_raw(ptr: Option<BoxedDynPtr>) -> *mut DynPtr {
+        ptr.map_or(core::ptr::null_mut(), BoxedDynPtr::into_raw)
+    }
+
+    /// # Safety
+    ///
+    /// `raw` must be null or satisfy the requirements of
+    /// [`BoxedDynPtr::from_raw`].
+    unsafe fn from_raw(raw: *mut DynPtr) -> Option<BoxedDynPtr> {
+        // Safety: The caller must uphold the requirements above.
+        (!raw.is_null()).then(|| unsafe { BoxedDynPtr::from_raw(raw) })
+    }
+}
+
+#[cfg(target_has_atomic = "ptr")]
+impl Default for AtomicDynPtr {
+    #[inline]
+    fn default() -> Self {
+        Self::empty()
+    }
+}
+
+#[cfg(target_has_atomic = "ptr")]
+impl Drop for AtomicDynPtr {
+    fn drop(&mut self) {
+        // Safety: We have exclusive access, and the slot owns its pointer.
+        drop(unsafe { Self::from_raw(*self.raw.get_mut()) });
+    }
+}
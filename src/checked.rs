@@ -0,0 +1,312 @@
+//! Non-panicking alternatives to the operations in this crate that can panic.
+//!
+//! Long-running programs that must not panic can limit themselves to the
+//! functions in this module, along with the other operations that never
+//! panic, such as [`Dyn::cast`](crate::Dyn::cast). The following table shows
+//! the non-panicking sibling of each operation that can panic:
+//!
+//! | Panicking                                  | Non-panicking           |
+//! |--------------------------------------------|-------------------------|
+//! | [`Dyn::new`](crate::Dyn::new)              | [`try_new`]             |
+//! | [`DynMut::new`](crate::DynMut::new)        | [`try_new_mut`]         |
+//! | [`DynPtr::new`](crate::DynPtr::new)        | [`try_new_ptr`]         |
+//! | [`StaticDyn::new`](crate::StaticDyn::new)  | [`try_new_static`]      |
+//! | [`DynDebug::new`](crate::DynDebug::new)    | [`try_new_debug`]       |
+//! | [`DynTagged::new`](crate::DynTagged::new)  | [`try_new`], then [`DynTagged::from_dyn`](crate::DynTagged::from_dyn) |
+//! | [`DynSet::from_views`](crate::DynSet::from_views) | [`try_new`] for each view, then [`DynSet::new`](crate::DynSet::new) |
+//! | [`Dyn::recast`](crate::Dyn::recast)        | [`try_recast`]          |
+//! | `DynBox::new`                              | `try_new_box`           |
+//! | `DynBox::new_cloneable`                    | `try_new_cloneable`     |
+//! | `DynPinBox::new`                           | `try_new_pin_box`       |
+//! | `StaticDyn::leak_arc`                      | `try_leak_arc`          |
+//! | [`Dyn::expect_cast`](crate::Dyn::expect_cast) | [`cast_checked`]     |
+//! | [`DynMut::expect_cast`](crate::DynMut::expect_cast) | [`cast_checked_mut`] |
+//! | [`DynPtr::expect_cast`](crate::DynPtr::expect_cast) | [`DynPtr::cast`](crate::DynPtr::cast) |
+//!
+//! The rows without links are available only when the `alloc` feature is
+//! enabled.
+//!
+//! Every constructor in this crate that erases a trait object type of its
+//! own, including those not listed above such as
+//! [`Dyn::new_error`](crate::Dyn::new_error),
+//! [`ArenaDyn::alloc`](crate::ArenaDyn::alloc), and the [`IntoDyn`](crate::IntoDyn)
+//! implementations, panics only if [`verify_layout_assumption`] fails for
+//! that type. Checking it first is therefore enough to rule out the panic.
+//!
+//! Indexing a `TraitTable` can also panic;
+//! use its `get` method instead.
+//!
+//! When debug assertions are enabled, [`DynPtr::cast`](crate::DynPtr::cast)
+//! also panics if it detects that its pointer is misaligned or has been
+//! invalidated. Those checks only detect situations that would otherwise be
+//! undefined behavior, so they have no non-panicking equivalent.
+
+use core::{fmt, ptr::NonNull};
+
+#[cfg(feature = "alloc")]
+use alloc::{boxed::Box, sync::Arc};
+#[cfg(feature = "alloc")]
+use core::{marker::Unsize, pin::Pin};
+
+#[cfg(feature = "alloc")]
+use crate::{DynBox, DynPinBox};
+use crate::{DynDebug, DynMut, DynPtr, DynTypeId, StaticDyn, TraitObject};
+
+/// Returns `true` if the trait object metadata for `Dyn` has the layout this
+/// crate relies on, as described in the crate-level documentation.
+///
+/// The constructors in this crate panic if this returns `false`, while those
+/// in this module return [`None`] instead. This is equivalent to
+/// [`DynPtr::layout_assumption_holds`].
+///
+/// ```
+/// # use any_dyn::checked::verify_layout_assumption;
+/// # use core::fmt::Debug;
+/// assert!(verify_layout_assumption::<dyn Debug>());
+/// ```
+#[inline]
+pub const fn verify_layout_assumption<Dyn: TraitObject + ?Sized + 'static>() -> bool {
+    DynPtr::layout_assumption_holds::<Dyn>()
+}
+
+/// Like [`Dyn::new`](crate::Dyn::new), but returns [`None`] instead of
+/// panicking if [`verify_layout_assumption`] fails for `Dyn`.
+///
+/// ```
+/// # use any_dyn::checked::try_new;
+/// # use core::fmt::Debug;
+/// let value = 5;
+/// let erased = try_new(&value as &dyn Debug).unwrap();
+/// assert!(erased.cast::<dyn Debug>().is_some());
+/// ```
+#[inline]
+pub const fn try_new<'a, Dyn: TraitObject + ?Sized + 'static>(
+    from: &'a Dyn,
+) -> Option<crate::Dyn<'a>> {
+    if !verify_layout_assumption::<Dyn>() {
+        return None;
+    }
+    Some(crate::Dyn::new(from))
+}
+
+/// Like [`DynMut::new`], but returns [`None`] instead of panicking if
+/// [`verify_layout_assumption`] fails for `Dyn`.
+#[inline]
+pub const fn try_new_mut<'a, Dyn: TraitObject + ?Sized + 'static>(
+    from: &'a mut Dyn,
+) -> Option<DynMut<'a>> {
+    if !verify_layout_assumption::<Dyn>() {
+        return None;
+    }
+    Some(DynMut::new(from))
+}
+
+/// Like [`DynPtr::new`], but returns [`None`] instead of panicking if
+/// [`verify_layout_assumption`] fails for `Dyn`.
+#[inline]
+pub const fn try_new_ptr<Dyn: TraitObject + ?Sized + 'static>(
+    from: NonNull<Dyn>,
+) -> Option<DynPtr> {
+    if !verify_layout_assumption::<Dyn>() {
+        return None;
+    }
+    Some(DynPtr::new(from))
+}
+
+/// Like [`StaticDyn::new`], but returns [`None`] instead of panicking if
+/// [`verify_layout_assumption`] fails for `Dyn`.
+#[inline]
+pub const fn try_new_static<Dyn: TraitObject + Sync + ?Sized + 'static>(
+    from: &'static Dyn,
+) -> Option<StaticDyn> {
+    if !verify_layout_assumption::<Dyn>() {
+        return None;
+    }
+    Some(StaticDyn::new(from))
+}
+
+/// Like [`DynDebug::new`], but returns [`None`] instead of panicking if
+/// [`verify_layout_assumption`] fails for `dyn Debug`.
+#[inline]
+pub const fn try_new_debug<'a>(from: &'a (dyn fmt::Debug + 'static)) -> Option<DynDebug<'a>> {
+    if !verify_layout_assumption::<dyn fmt::Debug>() {
+        return None;
+    }
+    Some(DynDebug::new(from))
+}
+
+/// Like [`Dyn::recast`](crate::Dyn::recast), but also returns [`None`]
+/// instead of panicking if [`verify_layout_assumption`] fails for `Super`.
+///
+/// `f` is not called in that case.
+///
+/// ```
+/// # use any_dyn::{Dyn, checked::try_recast};
+/// # use core::{any::Any, fmt::Debug};
+/// let value = 5_u32;
+/// let erased = Dyn::new(&value as &dyn Any);
+/// let debug = try_recast(erased, |any: &dyn Any| {
+///     any.downcast_ref::<u32>().unwrap() as &dyn Debug
+/// })
+/// .unwrap();
+/// assert!(debug.cast::<dyn Debug>().is_some());
+/// ```
+#[inline]
+pub fn try_recast<'a, Sub, Super>(
+    obj: crate::Dyn<'a>,
+    f: impl FnOnce(&'a Sub) -> &'a Super,
+) -> Option<crate::Dyn<'a>>
+where
+    Sub: TraitObject + ?Sized + 'static,
+    Super: TraitObject + ?Sized + 'static,
+{
+    let sub = obj.cast::<Sub>()?;
+    try_new(f(sub))
+}
+
+/// Like [`DynBox::new`], but returns the box unchanged instead of panicking
+/// if [`verify_layout_assumption`] fails for `Dyn`.
+///
+/// ```
+/// # use any_dyn::checked::try_new_box;
+/// # use core::fmt::Display;
+/// let Ok(boxed) = try_new_box(Box::new(5) as Box<dyn Display>) else {
+///     unreachable!();
+/// };
+/// assert_eq!(boxed.cast::<dyn Display>().unwrap().to_string(), "5");
+/// ```
+///
+/// This is available only when the `alloc` feature is enabled.
+#[cfg(feature = "alloc")]
+#[inline]
+pub fn try_new_box<Dyn: TraitObject + ?Sized + 'static>(
+    value: Box<Dyn>,
+) -> Result<DynBox, Box<Dyn>> {
+    if !verify_layout_assumption::<Dyn>() {
+        return Err(value);
+    }
+    Ok(DynBox::new(value))
+}
+
+/// Like [`DynBox::new_cloneable`], but returns the value unchanged instead
+/// of panicking if [`verify_layout_assumption`] fails for `Dyn`.
+///
+/// This is available only when the `alloc` feature is enabled.
+#[cfg(feature = "alloc")]
+#[inline]
+pub fn try_new_cloneable<T, Dyn>(value: T) -> Result<DynBox, T>
+where
+    T: Clone + Unsize<Dyn> + 'static,
+    Dyn: TraitObject + ?Sized + 'static,
+{
+    if !verify_layout_assumption::<Dyn>() {
+        return Err(value);
+    }
+    Ok(DynBox::new_cloneable::<T, Dyn>(value))
+}
+
+/// Like [`DynPinBox::new`], but returns the box unchanged instead of
+/// panicking if [`verify_layout_assumption`] fails for `Dyn`.
+///
+/// This is available only when the `alloc` feature is enabled.
+#[cfg(feature = "alloc")]
+#[inline]
+pub fn try_new_pin_box<Dyn: TraitObject + ?Sized + 'static>(
+    value: Pin<Box<Dyn>>,
+) -> Result<DynPinBox, Pin<Box<Dyn>>> {
+    if !verify_layout_assumption::<Dyn>() {
+        return Err(value);
+    }
+    Ok(DynPinBox::new(value))
+}
+
+/// Like [`StaticDyn::leak_arc`], but returns the [`Arc`] unchanged, without
+/// leaking it, instead of panicking if [`verify_layout_assumption`] fails for
+/// `Dyn`.
+///
+/// This is available only when the `alloc` feature is enabled.
+#[cfg(feature = "alloc")]
+#[inline]
+pub fn try_leak_arc<Dyn: TraitObject + Sync + ?Sized + 'static>(
+    from: Arc<Dyn>,
+) -> Result<StaticDyn, Arc<Dyn>> {
+    if !verify_layout_assumption::<Dyn>() {
+        return Err(from);
+    }
+    Ok(StaticDyn::leak_arc(from))
+}
+
+/// Like [`Dyn::expect_cast`](crate::Dyn::expect_cast), but returns a
+/// [`CastError`] describing the mismatch instead of panicking.
+///
+/// ```
+/// # use any_dyn::{Dyn, checked::cast_checked};
+/// # use core::fmt::{Debug, Display};
+/// let value = 5;
+/// let erased = Dyn::new(&value as &dyn Debug);
+/// assert!(cast_checked::<dyn Debug>(erased).is_ok());
+///
+/// let Err(err) = cast_checked::<dyn Display>(erased) else {
+///     unreachable!();
+/// };
+/// assert_eq!(err.actual(), erased.dyn_type_id());
+/// assert_eq!(err.to_string(), "not a trait object of type dyn core::fmt::Display");
+/// ```
+#[inline]
+pub fn cast_checked<'a, Dyn: TraitObject + ?Sized + 'static>(
+    obj: crate::Dyn<'a>,
+) -> Result<&'a Dyn, CastError> {
+    obj.cast::<Dyn>()
+        .ok_or_else(|| CastError::new::<Dyn>(obj.dyn_type_id()))
+}
+
+/// Like [`DynMut::expect_cast`], but returns a [`CastError`] describing the
+/// mismatch instead of panicking.
+#[inline]
+pub fn cast_checked_mut<'a, Dyn: TraitObject + ?Sized + 'static>(
+    obj: DynMut<'a>,
+) -> Result<&'a mut Dyn, CastError> {
+    let actual = obj.dyn_type_id();
+    obj.cast::<Dyn>()
+        .ok_or_else(|| CastError::new::<Dyn>(actual))
+}
+
+/// The error returned by [`cast_checked`] and [`cast_checked_mut`] when the
+/// requested trait object type does not match.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CastError {
+    expected: DynTypeId,
+    expected_name: &'static str,
+    actual: DynTypeId,
+}
+
+impl CastError {
+    fn new<Dyn: TraitObject + ?Sized + 'static>(actual: DynTypeId) -> Self {
+        Self {
+            expected: DynTypeId::of::<Dyn>(),
+            expected_name: core::any::type_name::<Dyn>(),
+            actual,
+        }
+    }
+
+    /// Returns the [`DynTypeId`] of the trait object type that was requested.
+    #[inline]
+    pub fn expected(&self) -> DynTypeId {
+        self.expected
+    }
+
+    /// Returns the [`DynTypeId`] of the trait object type the handle was
+    /// actually constructed from.
+    #[inline]
+    pub fn actual(&self) -> DynTypeId {
+        self.actual
+    }
+}
+
+impl fmt::Display for CastError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "not a trait object of type {}", self.expected_name)
+    }
+}
+
+impl core::error::Error for CastError {}
@@ -0,0 +1,187 @@
+use alloc::boxed::Box;
+use core::{marker::Unsize, ptr::NonNull};
+
+use crate::{Dyn, DynMut, DynPtr, DynTypeId, TraitObject};
+
+/// An owned, boxed trait object for an erased trait tracked only at runtime.
+///
+/// This is like `Box<dyn Trait>`, but with `Trait` tracked dynamically
+/// instead of statically. The object is dropped and its memory freed when the
+/// [`DynBox`] is dropped, even though the trait object type is not known
+/// statically at that point.
+///
+/// ```
+/// # use any_dyn::DynBox;
+/// # use core::fmt::Display;
+/// let boxed = DynBox::new(Box::new(5) as Box<dyn Display>);
+/// assert_eq!(boxed.cast::<dyn Display>().unwrap().to_string(), "5");
+/// ```
+///
+/// This is available only when the `alloc` feature is enabled.
+pub struct DynBox {
+    ptr: DynPtr,
+    drop_box: unsafe fn(DynPtr),
+    clone_box: Option<unsafe fn(&DynPtr) -> DynPtr>,
+}
+
+impl DynBox {
+    /// Creates a [`DynBox`] that takes ownership of the given boxed trait
+    /// object.
+    #[inline]
+    #[track_caller]
+    pub fn new<Dyn: TraitObject + ?Sized + 'static>(value: Box<Dyn>) -> Self {
+        Self {
+            ptr: Self::erase_box(value),
+            drop_box: Self::drop_box::<Dyn>,
+            clone_box: None,
+        }
+    }
+
+    /// Creates a [`DynBox`] that takes ownership of the given value as a
+    /// trait object of type `Dyn`, and that can later be cloned using
+    /// [`DynBox::try_clone`].
+    ///
+    /// The concrete type `T` is known only during this call, so this captures
+    /// a function for cloning a `T` and stores it alongside the trait object.
+    /// [`DynBox::try_clone`] then uses that function to make a deep copy,
+    /// without needing to know either the concrete type or the trait.
+    ///
+    /// ```
+    /// # use any_dyn::DynBox;
+    /// # use core::fmt::Display;
+    /// let original = DynBox::new_cloneable::<_, dyn Display>(String::from("hello"));
+    /// let copy = original.try_clone().unwrap();
+    /// drop(original);
+    /// assert_eq!(copy.cast::<dyn Display>().unwrap().to_string(), "hello");
+    /// ```
+    #[inline]
+    #[track_caller]
+    pub fn new_cloneable<T, Dyn>(value: T) -> Self
+    where
+        T: Clone + Unsize<Dyn> + 'static,
+        Dyn: TraitObject + ?Sized + 'static,
+    {
+        let value: Box<T> = Box::new(value);
+        Self {
+            ptr: Self::erase_box::<Dyn>(value),
+            drop_box: Self::drop_box::<Dyn>,
+            clone_box: Some(Self::clone_box::<T, Dyn>),
+        }
+    }
+
+    /// Returns a deep copy of the trait object if this [`DynBox`] was created
+    /// using [`DynBox::new_cloneable`], or [`None`] otherwise.
+    pub fn try_clone(&self) -> Option<Self> {
+        let clone_box = self.clone_box?;
+        Some(Self {
+            // Safety: clone_box was chosen for the same concrete type and
+            // trait object type as our pointer.
+            ptr: unsafe { clone_box(&self.ptr) },
+            drop_box: self.drop_box,
+            clone_box: self.clone_box,
+        })
+    }
+
+    /// Returns a reference to the trait object of type `Dyn` if and only if
+    /// this [`DynBox`] was created from a trait object of the same type.
+    #[inline]
+    pub fn cast<Dyn: TraitObject + ?Sized + 'static>(&self) -> Option<&Dyn> {
+        self.as_dyn().cast::<Dyn>()
+    }
+
+    /// Returns a mutable reference to the trait object of type `Dyn` if and
+    /// only if this [`DynBox`] was created from a trait object of the same
+    /// type.
+    #[inline]
+    pub fn cast_mut<Dyn: TraitObject + ?Sized + 'static>(&mut self) -> Option<&mut Dyn> {
+        self.as_dyn_mut().cast::<Dyn>()
+    }
+
+    /// Converts back into the original boxed trait object if and only if this
+    /// [`DynBox`] was created from a trait object of type `Dyn`, or returns
+    /// `self` unchanged otherwise.
+    ///
+    /// This allows calling methods that take `self: Box<Self>`.
+    #[inline]
+    pub fn downcast<Dyn: TraitObject + ?Sized + 'static>(self) -> Result<Box<Dyn>, Self> {
+        let Some(ptr) = self.ptr.cast::<Dyn>() else {
+            return Err(self);
+        };
+        // We're transferring ownership of the object to the result, so we
+        // must not drop it ourselves.
+        core::mem::forget(self);
+        // Safety: The pointer came from Box::into_raw for a Box<Dyn>.
+        Ok(unsafe { Box::from_raw(ptr.as_ptr()) })
+    }
+
+    /// Returns a [`Dyn`] borrowing the owned trait object.
+    #[inline]
+    pub fn as_dyn(&self) -> Dyn<'_> {
+        // Safety: The result borrows self, which owns the object.
+        unsafe { Dyn::from_raw(self.ptr) }
+    }
+
+    /// Returns a [`DynMut`] exclusively borrowing the owned trait object.
+    #[inline]
+    pub fn as_dyn_mut(&mut self) -> DynMut<'_> {
+        // Safety: The result exclusively borrows self, which owns the object.
+        unsafe { DynMut::from_raw(self.ptr) }
+    }
+
+    /// Returns the [`DynTypeId`] of the trait object type this box was
+    /// created from.
+    #[inline]
+    pub fn dyn_type_id(&self) -> DynTypeId {
+        self.ptr.dyn_type_id()
+    }
+
+    #[track_caller]
+    fn erase_box<Dyn: TraitObject + ?Sized + 'static>(value: Box<Dyn>) -> DynPtr {
+        // Safety: Box::into_raw never returns a null pointer.
+        DynPtr::new(unsafe { NonNull::new_unchecked(Box::into_raw(value)) })
+    }
+
+    /// # Safety
+    ///
+    /// `ptr` must have been created by [`DynBox::erase_box`] for the same
+    /// trait object type, and must not be used again afterwards.
+    unsafe fn drop_box<Dyn: TraitObject + ?Sized + 'static>(ptr: DynPtr) {
+        // Safety: The caller must ensure the pointer came from a Box<Dyn>.
+        unsafe {
+            let ptr = ptr.cast::<Dyn>().unwrap_unchecked();
+            drop(Box::from_raw(ptr.as_ptr()));
+        }
+    }
+
+    /// # Safety
+    ///
+    /// `ptr` must refer to a live `T` that was erased as a trait object of
+    /// type `Dyn`.
+    unsafe fn clone_box<T, Dyn>(ptr: &DynPtr) -> DynPtr
+    where
+        T: Clone + Unsize<Dyn> + 'static,
+        Dyn: TraitObject + ?Sized + 'static,
+    {
+        // Safety: The caller must ensure the object is a live T.
+        let value = unsafe { ptr.thin.cast::<T>().as_ref() };
+        let value: Box<T> = Box::new(value.clone());
+        Self::erase_box::<Dyn>(value)
+    }
+}
+
+impl Drop for DynBox {
+    fn drop(&mut self) {
+        // Safety: drop_box was chosen for the same trait object type as our
+        // pointer, and we won't use the pointer again.
+        unsafe { (self.drop_box)(self.ptr) }
+    }
+}
+
+impl core::fmt::Debug for DynBox {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("DynBox")
+            .field("ptr", &self.ptr)
+            .field("cloneable", &self.clone_box.is_some())
+            .finish()
+    }
+}
@@ -0,0 +1,56 @@
+use core::fmt::Debug;
+
+use crate::Dyn;
+
+/// A [`Dyn`] that is known to represent a `dyn Debug` trait object, and so
+/// can implement [`Debug`] itself by forwarding to that object.
+///
+/// This is a convenience for the common case of type-erased objects that need
+/// to be printed, such as in log messages.
+///
+/// ```
+/// # use any_dyn::DynDebug;
+/// # use core::fmt::Debug;
+/// let value = Some(5);
+/// let erased = DynDebug::new(&value as &dyn Debug);
+/// assert_eq!(format!("{erased:?}"), "Some(5)");
+///
+/// // The underlying Dyn can be recovered if needed.
+/// assert!(erased.as_dyn().cast::<dyn Debug>().is_some());
+/// ```
+#[derive(Clone, Copy)]
+pub struct DynDebug<'a> {
+    inner: Dyn<'a>,
+}
+
+impl<'a> DynDebug<'a> {
+    /// Creates a [`DynDebug`] value that represents the given `dyn Debug`
+    /// trait object.
+    #[inline]
+    pub const fn new(from: &'a (dyn Debug + 'static)) -> Self {
+        Self {
+            inner: Dyn::new(from),
+        }
+    }
+
+    /// Returns the underlying [`Dyn`] for this trait object reference.
+    #[inline]
+    pub const fn as_dyn(self) -> Dyn<'a> {
+        self.inner
+    }
+}
+
+impl Debug for DynDebug<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        // DynDebug can only be constructed from a dyn Debug, so this
+        // cast cannot fail.
+        self.inner.expect_cast::<dyn Debug>().fmt(f)
+    }
+}
+
+impl<'a> From<DynDebug<'a>> for Dyn<'a> {
+    #[inline]
+    fn from(value: DynDebug<'a>) -> Self {
+        value.inner
+    }
+}
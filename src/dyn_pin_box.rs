@@ -0,0 +1,105 @@
+use alloc::boxed::Box;
+use core::pin::Pin;
+
+use crate::{Dyn, DynBox, DynTypeId, TraitObject};
+
+/// An owned, pinned, boxed trait object for an erased trait tracked only at
+/// runtime.
+///
+/// This is like `Pin<Box<dyn Trait>>`, but with `Trait` tracked dynamically
+/// instead of statically. The most common use is storing futures of various
+/// output types together, such as in a task list, and later recovering each
+/// as `Pin<Box<dyn Future<Output = T>>>` so that it can be polled.
+///
+/// ```
+/// # use any_dyn::DynPinBox;
+/// # use core::{future::Future, pin::Pin, task::{Context, Poll, Waker}};
+/// let tasks = [
+///     DynPinBox::new(Box::pin(async { 1_u32 }) as Pin<Box<dyn Future<Output = u32>>>),
+///     DynPinBox::new(Box::pin(async { "two" }) as Pin<Box<dyn Future<Output = &str>>>),
+/// ];
+///
+/// let mut cx = Context::from_waker(Waker::noop());
+/// for task in tasks {
+///     match task.downcast::<dyn Future<Output = u32>>() {
+///         Ok(mut fut) => assert_eq!(fut.as_mut().poll(&mut cx), Poll::Ready(1)),
+///         Err(task) => {
+///             let mut fut = task.downcast::<dyn Future<Output = &str>>().unwrap();
+///             assert_eq!(fut.as_mut().poll(&mut cx), Poll::Ready("two"));
+///         }
+///     }
+/// }
+/// ```
+///
+/// The object remains pinned for as long as it's owned by a [`DynPinBox`],
+/// so unlike [`DynBox`] there is no way to obtain an unpinned `&mut` reference
+/// to it or to convert it into an unpinned `Box`. It can only be recovered as
+/// `Pin<&mut Dyn>` or `Pin<Box<Dyn>>`.
+///
+/// This is available only when the `alloc` feature is enabled.
+#[derive(Debug)]
+pub struct DynPinBox {
+    inner: DynBox,
+}
+
+impl DynPinBox {
+    /// Creates a [`DynPinBox`] that takes ownership of the given pinned boxed
+    /// trait object.
+    #[inline]
+    #[track_caller]
+    pub fn new<Dyn: TraitObject + ?Sized + 'static>(value: Pin<Box<Dyn>>) -> Self {
+        // Safety: The resulting DynBox is never exposed, and we only give
+        // out pinned references to the object it owns.
+        let value = unsafe { Pin::into_inner_unchecked(value) };
+        Self {
+            inner: DynBox::new(value),
+        }
+    }
+
+    /// Returns a pinned reference to the trait object of type `Dyn` if and
+    /// only if this [`DynPinBox`] was created from a trait object of the same
+    /// type.
+    #[inline]
+    pub fn cast<Dyn: TraitObject + ?Sized + 'static>(&self) -> Option<Pin<&Dyn>> {
+        let obj = self.inner.cast::<Dyn>()?;
+        // Safety: The object has been pinned since it was given to us.
+        Some(unsafe { Pin::new_unchecked(obj) })
+    }
+
+    /// Returns a pinned mutable reference to the trait object of type `Dyn`
+    /// if and only if this [`DynPinBox`] was created from a trait object of
+    /// the same type.
+    #[inline]
+    pub fn cast_mut<Dyn: TraitObject + ?Sized + 'static>(&mut self) -> Option<Pin<&mut Dyn>> {
+        let obj = self.inner.cast_mut::<Dyn>()?;
+        // Safety: The object has been pinned since it was given to us.
+        Some(unsafe { Pin::new_unchecked(obj) })
+    }
+
+    /// Converts back into the original pinned boxed trait object if and only
+    /// if this [`DynPinBox`] was created from a trait object of type `Dyn`,
+    /// or returns `self` unchanged otherwise.
+    #[inline]
+    pub fn downcast<Dyn: TraitObject + ?Sized + 'static>(self) -> Result<Pin<Box<Dyn>>, Self> {
+        match self.inner.downcast::<Dyn>() {
+            Ok(value) => Ok(Box::into_pin(value)),
+            Err(inner) => Err(Self { inner }),
+        }
+    }
+
+    /// Returns a [`Dyn`] borrowing the owned trait object.
+    ///
+    /// Shared references to pinned objects don't allow moving them, so this
+    /// is safe even though the result is not itself pinned.
+    #[inline]
+    pub fn as_dyn(&self) -> Dyn<'_> {
+        self.inner.as_dyn()
+    }
+
+    /// Returns the [`DynTypeId`] of the trait object type this box was
+    /// created from.
+    #[inline]
+    pub fn dyn_type_id(&self) -> DynTypeId {
+        self.inner.dyn_type_id()
+    }
+}
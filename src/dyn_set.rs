@@ -0,0 +1,114 @@
+use crate::{Dyn, DynTypeId, IntoDyn, TraitObject};
+
+/// A fixed-size set of [`Dyn`] values, typically several trait object views
+/// of the same underlying object, that can be queried by trait.
+///
+/// This is for building a handle that offers a small, fixed set of traits
+/// without implementing [`AsTraitObject`](crate::traitcast::AsTraitObject).
+/// [`DynSet::from_views`] erases each element of a tuple of trait object
+/// references in one step:
+///
+/// ```
+/// # use any_dyn::DynSet;
+/// # use core::fmt::{Debug, Display};
+/// let x = 5_u32;
+/// let set = DynSet::from_views((&x as &dyn Debug, &x as &dyn Display));
+/// assert_eq!(set.len(), 2);
+/// assert_eq!(set.cast::<dyn Display>().unwrap().to_string(), "5");
+/// assert!(set.cast::<dyn core::error::Error>().is_none());
+/// ```
+///
+/// Looking up a trait is a linear search in the order the views were given,
+/// and the first view of the requested trait object type wins if there is
+/// more than one.
+#[derive(Debug, Clone, Copy)]
+pub struct DynSet<'a, const N: usize> {
+    views: [Dyn<'a>; N],
+}
+
+impl<'a, const N: usize> DynSet<'a, N> {
+    /// Creates a set from a tuple of trait object references, or of
+    /// already-erased [`Dyn`] values.
+    ///
+    /// Tuples of up to eight elements are supported.
+    #[inline]
+    #[track_caller]
+    pub fn from_views(views: impl DynViews<'a, N>) -> Self {
+        Self::new(views.into_dyns())
+    }
+
+    /// Creates a set from an array of already-erased trait objects.
+    #[inline]
+    pub const fn new(views: [Dyn<'a>; N]) -> Self {
+        Self { views }
+    }
+
+    /// Returns a reference to a trait object of type `Dyn` if and only if one
+    /// of the views in this set was constructed from a trait object of that
+    /// type.
+    #[inline]
+    pub fn cast<Dyn: TraitObject + ?Sized + 'static>(&self) -> Option<&'a Dyn> {
+        self.views.iter().find_map(|view| view.cast::<Dyn>())
+    }
+
+    /// Returns true if this set has a view of the trait object type
+    /// identified by `type_id`.
+    #[inline]
+    pub fn contains(&self, type_id: DynTypeId) -> bool {
+        self.views.iter().any(|view| view.dyn_type_id() == type_id)
+    }
+
+    /// Returns the number of views in the set.
+    #[inline]
+    pub const fn len(&self) -> usize {
+        N
+    }
+
+    /// Returns true if the set has no views.
+    #[inline]
+    pub const fn is_empty(&self) -> bool {
+        N == 0
+    }
+
+    /// Returns the views in the set, in the order they were given.
+    #[inline]
+    pub const fn as_slice(&self) -> &[Dyn<'a>] {
+        &self.views
+    }
+}
+
+/// A tuple of trait object references that [`DynSet::from_views`] can erase
+/// into a [`DynSet`] of `N` elements.
+///
+/// This is implemented for tuples of up to eight elements where each element
+/// implements [`IntoDyn`].
+///
+/// This trait is sealed, so it cannot be implemented outside of this crate.
+pub trait DynViews<'a, const N: usize>: crate::sealed::DynViewsSealed {
+    /// Erases each element of the tuple, in order.
+    fn into_dyns(self) -> [Dyn<'a>; N];
+}
+
+macro_rules! impl_dyn_views {
+    ($n:literal => $($t:ident $v:ident),+) => {
+        impl<'a, $($t: IntoDyn<'a>),+> DynViews<'a, $n> for ($($t,)+) {
+            #[inline]
+            #[track_caller]
+            fn into_dyns(self) -> [Dyn<'a>; $n] {
+                let ($($v,)+) = self;
+                [$($v.into_dyn()),+]
+            }
+        }
+
+        impl<'a, $($t: IntoDyn<'a>),+> crate::sealed::DynViewsSealed for ($($t,)+) {}
+    };
+}
+
+impl_dyn_views!(1 => A a);
+impl_dyn_views!(2 => A a, B b);
+impl_dyn_views!(3 => A a, B b, C c);
+impl_dyn_views!(4 => A a, B b, C c, D d);
+impl_dyn_views!(5 => A a, B b, C c, D d, E e);
+impl_dyn_views!(6 => A a, B b, C c, D d, E e, F f);
+impl_dyn_views!(7 => A a, B b, C c, D d, E e, F f, G g);
+impl_dyn_views!(8 => A a, B b, C c, D d, E e, F f, G g, H h);
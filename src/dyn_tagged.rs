@@ -0,0 +1,72 @@
+use crate::{Dyn, DynTypeId, TraitObject};
+
+/// A [`Dyn`] with an additional caller-defined tag, for situations where the
+/// same trait can be offered in more than one "role".
+///
+/// [`DynTagged::cast_tagged`] succeeds only if both the trait object type and
+/// the tag match, so a dispatch table can hold several entries for the same
+/// trait that are distinguished by their tags.
+///
+/// The meaning of the tag is entirely up to the caller. This library only
+/// compares tags for equality, and never interprets them in any other way.
+///
+/// ```
+/// # use any_dyn::DynTagged;
+/// # use core::fmt::Display;
+/// const TITLE: u64 = 1;
+/// const BODY: u64 = 2;
+///
+/// let title = DynTagged::new(&"Hello" as &dyn Display, TITLE);
+/// assert!(title.cast_tagged::<dyn Display>(TITLE).is_some());
+/// assert!(title.cast_tagged::<dyn Display>(BODY).is_none());
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct DynTagged<'a> {
+    inner: Dyn<'a>,
+    tag: u64,
+}
+
+impl<'a> DynTagged<'a> {
+    /// Creates a [`DynTagged`] value that represents the given trait object
+    /// reference with the given tag.
+    #[inline]
+    #[track_caller]
+    pub const fn new<Dyn: TraitObject + ?Sized + 'static>(from: &'a Dyn, tag: u64) -> Self {
+        Self::from_dyn(crate::Dyn::new(from), tag)
+    }
+
+    /// Attaches the given tag to an existing [`Dyn`].
+    #[inline]
+    pub const fn from_dyn(inner: Dyn<'a>, tag: u64) -> Self {
+        Self { inner, tag }
+    }
+
+    /// Attempts to cast to a specific trait object type, succeeding only if
+    /// both the trait object type and the tag match.
+    #[inline]
+    pub fn cast_tagged<Dyn: TraitObject + ?Sized + 'static>(self, tag: u64) -> Option<&'a Dyn> {
+        if self.tag != tag {
+            return None;
+        }
+        self.inner.cast()
+    }
+
+    /// Returns the tag that was given when this value was created.
+    #[inline]
+    pub const fn tag(self) -> u64 {
+        self.tag
+    }
+
+    /// Returns the [`DynTypeId`] for the trait object type this value
+    /// represents.
+    #[inline]
+    pub const fn dyn_type_id(self) -> DynTypeId {
+        self.inner.dyn_type_id()
+    }
+
+    /// Returns the underlying [`Dyn`], discarding the tag.
+    #[inline]
+    pub const fn as_dyn(self) -> Dyn<'a> {
+        self.inner
+    }
+}
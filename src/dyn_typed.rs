@@ -0,0 +1,179 @@
+use core::{fmt, marker::PhantomData, ptr::NonNull};
+
+use crate::{Dyn, DynMut, DynPtr, DynTypeId, TraitObject, reconstruct};
+
+/// A [`Dyn`] that is statically known to represent a trait object of type
+/// `T`.
+///
+/// Values of this type can be created only by [`Dyn::typed`], which performs
+/// the usual runtime check once. After that, [`DynTyped::get`] returns the
+/// trait object without checking again. This suits APIs that validate an
+/// erased handle once at a boundary and then pass a proven-typed handle
+/// inward, so that the inner code can't be given a handle of the wrong
+/// trait.
+///
+/// ```
+/// # use any_dyn::{Dyn, DynTyped};
+/// # use core::fmt::Display;
+/// fn render(item: DynTyped<'_, dyn Display>) -> String {
+///     item.get().to_string()
+/// }
+///
+/// let erased = Dyn::new(&42 as &dyn Display);
+/// let typed = erased.typed::<dyn Display>().expect("not a Display");
+/// assert_eq!(render(typed), "42");
+/// ```
+pub struct DynTyped<'a, T: TraitObject + ?Sized + 'static> {
+    inner: Dyn<'a>,
+    _phantom: PhantomData<&'a T>,
+}
+
+impl<'a, T: TraitObject + ?Sized + 'static> DynTyped<'a, T> {
+    /// Wraps `inner` without checking its trait object type.
+    ///
+    /// # Safety
+    ///
+    /// `inner` must have been constructed from a trait object of type `T`.
+    #[inline]
+    pub(crate) const unsafe fn new_unchecked(inner: Dyn<'a>) -> Self {
+        Self {
+            inner,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Returns the trait object this value represents.
+    ///
+    /// Unlike [`Dyn::cast`], this cannot fail and performs no runtime check.
+    #[inline]
+    pub fn get(&self) -> &'a T {
+        // Safety: new_unchecked's caller promised that the pointer is for
+        // a `T`, and `Dyn<'a>` promises that it's valid for shared access
+        // for `'a`.
+        unsafe { restore::<T>(&self.inner.as_ptr()).as_ref() }
+    }
+
+    /// Returns the underlying [`Dyn`], discarding the static type.
+    #[inline]
+    pub const fn as_dyn(&self) -> Dyn<'a> {
+        self.inner
+    }
+
+    /// Returns the [`DynTypeId`] of `T`.
+    #[inline]
+    pub const fn dyn_type_id(&self) -> DynTypeId {
+        self.inner.dyn_type_id()
+    }
+}
+
+impl<T: TraitObject + ?Sized + 'static> Clone for DynTyped<'_, T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T: TraitObject + ?Sized + 'static> Copy for DynTyped<'_, T> {}
+
+impl<T: TraitObject + ?Sized + 'static> fmt::Debug for DynTyped<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("DynTyped").field(&self.inner).finish()
+    }
+}
+
+/// A [`DynMut`] that is statically known to represent a trait object of type
+/// `T`.
+///
+/// This is the mutable counterpart of [`DynTyped`], created by
+/// [`DynMut::typed`].
+///
+/// ```
+/// # use any_dyn::{DynMut, DynTypedMut};
+/// # use core::fmt::Write;
+/// fn greet(mut out: DynTypedMut<'_, dyn Write>) {
+///     out.get_mut().write_str("hello").unwrap();
+/// }
+///
+/// let mut buf = String::new();
+/// let erased = DynMut::new(&mut buf as &mut dyn Write);
+/// greet(erased.typed::<dyn Write>().expect("not a Write"));
+/// assert_eq!(buf, "hello");
+/// ```
+pub struct DynTypedMut<'a, T: TraitObject + ?Sized + 'static> {
+    inner: DynMut<'a>,
+    _phantom: PhantomData<&'a mut T>,
+}
+
+impl<'a, T: TraitObject + ?Sized + 'static> DynTypedMut<'a, T> {
+    /// Wraps `inner` without checking its trait object type.
+    ///
+    /// # Safety
+    ///
+    /// `inner` must have been constructed from a trait object of type `T`.
+    #[inline]
+    pub(crate) const unsafe fn new_unchecked(inner: DynMut<'a>) -> Self {
+        Self {
+            inner,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Returns a shared reference to the trait object this value represents.
+    #[inline]
+    pub fn get(&self) -> &T {
+        // Safety: as for DynTyped::get, and we hold exclusive access for
+        // at least as long as the borrow of self.
+        unsafe { restore::<T>(&self.inner.as_ptr()).as_ref() }
+    }
+
+    /// Returns a mutable reference to the trait object this value
+    /// represents.
+    ///
+    /// Unlike [`DynMut::cast_exclusive`], this cannot fail and performs no
+    /// runtime check.
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut T {
+        // Safety: as for DynTyped::get, and the exclusive borrow of self
+        // prevents any other access for the lifetime of the result.
+        unsafe { restore::<T>(&self.inner.as_ptr()).as_mut() }
+    }
+
+    /// Returns the trait object this value represents for the full
+    /// lifetime `'a`, consuming the wrapper.
+    #[inline]
+    pub fn into_mut(self) -> &'a mut T {
+        // Safety: as for get_mut, and consuming self transfers its
+        // exclusive access to the result.
+        unsafe { restore::<T>(&self.inner.as_ptr()).as_mut() }
+    }
+
+    /// Returns the underlying [`DynMut`], discarding the static type.
+    #[inline]
+    pub const fn into_dyn_mut(self) -> DynMut<'a> {
+        self.inner
+    }
+
+    /// Returns the [`DynTypeId`] of `T`.
+    #[inline]
+    pub const fn dyn_type_id(&self) -> DynTypeId {
+        self.inner.dyn_type_id()
+    }
+}
+
+impl<T: TraitObject + ?Sized + 'static> fmt::Debug for DynTypedMut<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("DynTypedMut").field(&self.inner).finish()
+    }
+}
+
+/// Rebuilds the trait object pointer from `ptr` without checking its type.
+///
+/// # Safety
+///
+/// `ptr` must have been constructed from a trait object of type `T`.
+#[inline]
+unsafe fn restore<T: TraitObject + ?Sized + 'static>(ptr: &DynPtr) -> NonNull<T> {
+    debug_assert!(ptr.dyn_type_id() == DynTypeId::of::<T>());
+    // Safety: The caller promises that the metadata is for `T`.
+    reconstruct(ptr.thin, unsafe { ptr.metadata.restore::<T>() })
+}
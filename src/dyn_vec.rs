@@ -0,0 +1,108 @@
+use alloc::vec::Vec;
+
+use crate::{Dyn, TraitObject};
+
+/// A collection of [`Dyn`] values that may each represent a trait object of a
+/// different trait.
+///
+/// This is similar to a `Vec<Dyn<'a>>`, but additionally offers
+/// [`DynVec::iter_as`] for visiting only the elements that can be cast to a
+/// particular trait object type.
+///
+/// ```
+/// # use any_dyn::{Dyn, DynVec};
+/// trait WithMessage {
+///     fn message(&self) -> &'static str;
+/// }
+///
+/// trait WithIndex {
+///     fn index(&self) -> usize;
+/// }
+///
+/// struct Hello;
+/// impl WithMessage for Hello {
+///     fn message(&self) -> &'static str {
+///         "Hello, world!"
+///     }
+/// }
+///
+/// struct Fifth;
+/// impl WithIndex for Fifth {
+///     fn index(&self) -> usize {
+///         5
+///     }
+/// }
+///
+/// let mut objs = DynVec::new();
+/// objs.push(Dyn::new(&Hello as &dyn WithMessage));
+/// objs.push(Dyn::new(&Fifth as &dyn WithIndex));
+/// objs.push(Dyn::new(&Hello as &dyn WithMessage));
+///
+/// let messages: Vec<_> = objs.iter_as::<dyn WithMessage>().map(|o| o.message()).collect();
+/// let indices: Vec<_> = objs.iter_as::<dyn WithIndex>().map(|o| o.index()).collect();
+/// assert_eq!(messages, ["Hello, world!", "Hello, world!"]);
+/// assert_eq!(indices, [5]);
+/// ```
+///
+/// This is available only when the `alloc` feature is enabled.
+#[derive(Debug, Clone, Default)]
+pub struct DynVec<'a> {
+    elems: Vec<Dyn<'a>>,
+}
+
+impl<'a> DynVec<'a> {
+    /// Creates an empty [`DynVec`].
+    #[inline]
+    pub const fn new() -> Self {
+        Self { elems: Vec::new() }
+    }
+
+    /// Appends a trait object to the end of the collection.
+    #[inline]
+    pub fn push(&mut self, elem: Dyn<'a>) {
+        self.elems.push(elem);
+    }
+
+    /// Returns the number of trait objects in the collection, regardless of
+    /// their traits.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.elems.len()
+    }
+
+    /// Returns `true` if the collection contains no trait objects at all.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.elems.is_empty()
+    }
+
+    /// Returns an iterator over all of the type-erased trait objects in the
+    /// collection, in the order they were added.
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = Dyn<'a>> + '_ {
+        self.elems.iter().copied()
+    }
+
+    /// Returns an iterator over only the elements that were constructed from a
+    /// trait object of type `Dyn`, in the order they were added.
+    #[inline]
+    pub fn iter_as<Dyn: TraitObject + ?Sized + 'static>(
+        &self,
+    ) -> impl Iterator<Item = &'a Dyn> + '_ {
+        self.elems.iter().filter_map(|elem| elem.cast::<Dyn>())
+    }
+}
+
+impl<'a> FromIterator<Dyn<'a>> for DynVec<'a> {
+    fn from_iter<T: IntoIterator<Item = Dyn<'a>>>(iter: T) -> Self {
+        Self {
+            elems: Vec::from_iter(iter),
+        }
+    }
+}
+
+impl<'a> Extend<Dyn<'a>> for DynVec<'a> {
+    fn extend<T: IntoIterator<Item = Dyn<'a>>>(&mut self, iter: T) {
+        self.elems.extend(iter);
+    }
+}
@@ -0,0 +1,177 @@
+use alloc::vec::Vec;
+
+use crate::{Dyn, DynBox, DynMut, TraitObject};
+
+/// An identifier for an object stored in a [`HandleArena`].
+///
+/// Each identifier includes the generation of the slot it refers to, so an
+/// identifier for an object that has since been removed never refers to a
+/// different object that later reuses the same slot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct HandleId {
+    index: u32,
+    generation: u32,
+}
+
+impl HandleId {
+    /// Returns the index of the slot this identifier refers to.
+    #[inline]
+    pub const fn index(self) -> u32 {
+        self.index
+    }
+
+    /// Returns the generation of the slot this identifier refers to.
+    #[inline]
+    pub const fn generation(self) -> u32 {
+        self.generation
+    }
+}
+
+/// A collection of owned, type-erased trait objects, each identified by a
+/// [`HandleId`].
+///
+/// This is a generational arena: the slot of a removed object can be reused
+/// for a later object, but each reuse advances the slot's generation so that
+/// identifiers for the removed object no longer match. This makes it suitable
+/// for long-running programs that give out handles to external callers and
+/// cannot trust them to stop using a handle after the object is removed.
+///
+/// ```
+/// # use any_dyn::{DynBox, HandleArena};
+/// # use core::fmt::Display;
+/// let mut arena = HandleArena::new();
+/// let a = arena.insert(DynBox::new(Box::new(1) as Box<dyn Display>));
+/// let b = arena.insert(DynBox::new(Box::new("two") as Box<dyn Display>));
+/// assert_eq!(arena.cast::<dyn Display>(b).unwrap().to_string(), "two");
+///
+/// arena.remove(a);
+/// let c = arena.insert(DynBox::new(Box::new(3.0) as Box<dyn Display>));
+/// assert!(arena.get(a).is_none());
+/// assert_eq!(arena.cast::<dyn Display>(c).unwrap().to_string(), "3");
+/// ```
+///
+/// Generations are 32-bit and wrap around, so an identifier could in
+/// principle match again after its slot has been reused more than four
+/// billion times.
+///
+/// This is available only when the `alloc` feature is enabled.
+#[derive(Debug, Default)]
+pub struct HandleArena {
+    slots: Vec<Slot>,
+    free: Vec<u32>,
+    len: usize,
+}
+
+#[derive(Debug)]
+struct Slot {
+    generation: u32,
+    value: Option<DynBox>,
+}
+
+impl HandleArena {
+    /// Creates an empty [`HandleArena`].
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            slots: Vec::new(),
+            free: Vec::new(),
+            len: 0,
+        }
+    }
+
+    /// Adds an object to the arena, returning its identifier.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the arena already has [`u32::MAX`] slots.
+    pub fn insert(&mut self, value: DynBox) -> HandleId {
+        self.len += 1;
+        if let Some(index) = self.free.pop() {
+            let slot = &mut self.slots[index as usize];
+            slot.value = Some(value);
+            return HandleId {
+                index,
+                generation: slot.generation,
+            };
+        }
+        let index = u32::try_from(self.slots.len()).expect("too many slots in HandleArena");
+        self.slots.push(Slot {
+            generation: 0,
+            value: Some(value),
+        });
+        HandleId {
+            index,
+            generation: 0,
+        }
+    }
+
+    /// Removes and returns the object with the given identifier, if it's
+    /// still present.
+    pub fn remove(&mut self, id: HandleId) -> Option<DynBox> {
+        let slot = self.slot_mut(id)?;
+        let value = slot.value.take()?;
+        slot.generation = slot.generation.wrapping_add(1);
+        self.free.push(id.index);
+        self.len -= 1;
+        Some(value)
+    }
+
+    /// Returns a [`Dyn`] borrowing the object with the given identifier, if
+    /// it's still present.
+    #[inline]
+    pub fn get(&self, id: HandleId) -> Option<Dyn<'_>> {
+        let slot = self.slots.get(id.index as usize)?;
+        if slot.generation != id.generation {
+            return None;
+        }
+        slot.value.as_ref().map(DynBox::as_dyn)
+    }
+
+    /// Returns a [`DynMut`] exclusively borrowing the object with the given
+    /// identifier, if it's still present.
+    #[inline]
+    pub fn get_mut(&mut self, id: HandleId) -> Option<DynMut<'_>> {
+        self.slot_mut(id)?.value.as_mut().map(DynBox::as_dyn_mut)
+    }
+
+    /// Returns a reference to the object with the given identifier as a
+    /// trait object of type `Dyn`, if it's still present and was inserted as
+    /// a trait object of that type.
+    #[inline]
+    pub fn cast<Dyn: TraitObject + ?Sized + 'static>(&self, id: HandleId) -> Option<&Dyn> {
+        self.get(id)?.cast::<Dyn>()
+    }
+
+    /// Like [`HandleArena::cast`], but returns a mutable reference.
+    #[inline]
+    pub fn cast_mut<Dyn: TraitObject + ?Sized + 'static>(
+        &mut self,
+        id: HandleId,
+    ) -> Option<&mut Dyn> {
+        self.get_mut(id)?.cast::<Dyn>()
+    }
+
+    /// Returns `true` if the object with the given identifier is still
+    /// present.
+    #[inline]
+    pub fn contains(&self, id: HandleId) -> bool {
+        self.get(id).is_some()
+    }
+
+    /// Returns the number of objects in the arena.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the arena contains no objects.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    fn slot_mut(&mut self, id: HandleId) -> Option<&mut Slot> {
+        let slot = self.slots.get_mut(id.index as usize)?;
+        (slot.generation == id.generation).then_some(slot)
+    }
+}
@@ -0,0 +1,144 @@
+use alloc::{boxed::Box, collections::BTreeMap};
+use core::{any::TypeId, ptr::NonNull};
+use std::sync::Mutex;
+
+use crate::{DynPtr, DynTypeId, ErasedMetadata, TraitObject, reconstruct};
+
+/// A compact alternative to [`DynPtr`] that shares its trait object metadata
+/// with other handles for the same concrete type and trait.
+///
+/// A [`DynPtr`] stores the object's address, its trait object metadata, the
+/// [`TypeId`] of its trait object type, and some bookkeeping, all inline.
+/// That's several words per handle, and for a large store of objects of only
+/// a few different types most of those words are identical from one handle
+/// to the next. [`InternedDynPtr`] instead stores only the object's address
+/// and a reference to a shared copy of everything else, so it's
+/// always exactly two pointers in size:
+///
+/// ```
+/// # use any_dyn::{DynPtr, InternedDynPtr};
+/// assert_eq!(size_of::<InternedDynPtr>(), 2 * size_of::<usize>());
+/// assert!(size_of::<DynPtr>() > size_of::<InternedDynPtr>());
+/// ```
+///
+/// At the time of writing, a [`DynPtr`] is 32 bytes on a 64-bit target when
+/// none of the debugging features are enabled, and an [`InternedDynPtr`] is
+/// 16 bytes. The shared copies are allocated in a process-global table the
+/// first time each distinct trait object metadata value is interned, and
+/// are never freed.
+///
+/// [`InternedDynPtr::cast`] costs one extra pointer indirection compared to
+/// [`DynPtr::cast`], to reach the shared copy of the metadata, and does not
+/// consult the global table at all. Only [`InternedDynPtr::new`] does that,
+/// and it must take a lock to do so, so interning is considerably more
+/// expensive than constructing a [`DynPtr`]. This type is therefore
+/// worthwhile only for handles that are stored for a long time relative to
+/// how often new ones are created.
+///
+/// ```
+/// # use any_dyn::{DynPtr, InternedDynPtr};
+/// # use core::{fmt::Display, ptr::NonNull};
+/// let values = [1, 2, 3];
+/// let handles: Vec<InternedDynPtr> = values
+///     .iter()
+///     .map(|v| InternedDynPtr::new(DynPtr::new(NonNull::from(v as &dyn Display))))
+///     .collect();
+/// let ptr = handles[2].cast::<dyn Display>().unwrap();
+/// // Safety: `values` is still live.
+/// assert_eq!(unsafe { ptr.as_ref() }.to_string(), "3");
+/// ```
+///
+/// Any debugging information attached to the original [`DynPtr`], such as
+/// its [`Generation`](crate::Generation), is discarded by interning.
+///
+/// This is available only when the `std` feature is enabled.
+#[derive(Debug, Clone, Copy)]
+pub struct InternedDynPtr {
+    thin: NonNull<()>,
+    shared: &'static SharedMetadata,
+}
+
+/// The parts of a [`DynPtr`] that are shared between all [`InternedDynPtr`]
+/// values for the same concrete type and trait.
+#[derive(Debug)]
+struct SharedMetadata {
+    metadata: ErasedMetadata,
+    type_id: TypeId,
+}
+
+/// The process-global intern table, keyed by trait object type and vtable
+/// address.
+static INTERNED: Mutex<BTreeMap<(TypeId, usize), &'static SharedMetadata>> =
+    Mutex::new(BTreeMap::new());
+
+impl InternedDynPtr {
+    /// Creates a compact handle for the same object as `ptr`, interning its
+    /// metadata if no earlier call has already done so.
+    pub fn new(ptr: DynPtr) -> Self {
+        let shared = SharedMetadata {
+            metadata: ptr.metadata,
+            type_id: ptr.type_id,
+        };
+        let vtable_addr = ptr.metadata.vtable_addr();
+        let shared = if vtable_addr == 0 {
+            // We can't tell whether two metadata values are equal without
+            // knowing their representation, so each one gets its own entry.
+            Box::leak(Box::new(shared))
+        } else {
+            let mut table = INTERNED.lock().unwrap_or_else(|err| err.into_inner());
+            *table
+                .entry((ptr.type_id, vtable_addr))
+                .or_insert_with(|| Box::leak(Box::new(shared)))
+        };
+        Self {
+            thin: ptr.thin,
+            shared,
+        }
+    }
+
+    /// Returns a pointer to a trait object of type `Dyn` if and only if this
+    /// handle was interned from a [`DynPtr`] constructed from a trait object
+    /// of the same type.
+    ///
+    /// This has the same provenance guarantees as [`DynPtr::cast`].
+    #[inline]
+    pub fn cast<Dyn: TraitObject + ?Sized + 'static>(&self) -> Option<NonNull<Dyn>> {
+        if TypeId::of::<Dyn>() != self.shared.type_id {
+            return None;
+        }
+        // Safety: The shared metadata was copied from a DynPtr for the
+        // same trait object type, which we've just checked.
+        let metadata = unsafe { self.shared.metadata.restore::<Dyn>() };
+        Some(reconstruct(self.thin, metadata))
+    }
+
+    /// Returns the [`DynTypeId`] of the trait object type this handle was
+    /// interned from.
+    #[inline]
+    pub fn dyn_type_id(&self) -> DynTypeId {
+        DynTypeId {
+            type_id: self.shared.type_id,
+        }
+    }
+
+    /// Returns true if `self` and `other` share the same interned metadata,
+    /// which is usually the case for handles created from the same concrete
+    /// type erased as the same trait object type.
+    ///
+    /// Rust does not guarantee that there is only one vtable for each
+    /// combination of concrete type and trait, and handles whose metadata
+    /// refers to different copies of the vtable are interned separately. This
+    /// is therefore useful only as a diagnostic, and not for deciding whether
+    /// two handles refer to objects of the same type.
+    #[inline]
+    pub fn shares_metadata_with(&self, other: &Self) -> bool {
+        core::ptr::eq(self.shared, other.shared)
+    }
+}
+
+impl From<DynPtr> for InternedDynPtr {
+    #[inline]
+    fn from(ptr: DynPtr) -> Self {
+        Self::new(ptr)
+    }
+}
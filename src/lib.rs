@@ -23,10 +23,35 @@
 //! This library depends only on `core`, so it can be used as a dependency of
 //! `no_std` callers.
 //!
+//! # Optional features
+//!
+//! - `alloc`: additional helpers that depend on the `alloc` crate, such as
+//!   implementations for smart pointer types.
+//! - `std`: additional helpers that depend on the standard library, such as
+//!   `traitcast::ExternalCaster`. Implies `alloc`.
+//! - `debug-diagnostics`: each [`DynPtr`] remembers the source location where
+//!   it was constructed, which is then included in the panic message when an
+//!   `expect_cast` call fails. This makes each [`DynPtr`] larger, so it's
+//!   intended only for use while debugging.
+//! - `debug-validity`: adds `Generation` and `with_generation` methods on
+//!   [`DynPtr`], [`Dyn`] and [`DynMut`], which together can detect the use of
+//!   a handle whose object may have moved or been dropped. The check is
+//!   performed only when debug assertions are enabled, and release builds
+//!   behave exactly as without this feature.
+//! - `minimal-panic`: panics raised by this library use only fixed messages
+//!   without any formatting, so that the formatting machinery is not needed
+//!   on size-constrained targets. This takes priority over
+//!   `debug-diagnostics`, so the messages are less informative.
+//! - `downcast-rs`: adds `traitcast::as_trait_object_via_downcast`, for
+//!   bridging types that use the [`downcast-rs`](https://docs.rs/downcast-rs)
+//!   crate's `Downcast` trait. Implies `alloc`.
+//!
 //! # WARNING: This relies on Rust implementation details!
 //!
 //! The current implementation of type-erased trait object references relies on
 //! the unstable `ptr_metadata` feature, and so can only work on nightly Rust.
+//! `DynBox::new_cloneable` additionally relies on the unstable `unsize`
+//! feature.
 //! More importantly, it relies on a specific implementation detail that is not
 //! actually guaranteed at the time of writing: that the metadata for trait
 //! objects always has the same size and alignment regardless of which trait is
@@ -50,17 +75,139 @@
 //! **If that situation bothers you, then do not use this library!**
 #![no_std]
 #![feature(ptr_metadata)]
+#![cfg_attr(feature = "alloc", feature(unsize))]
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+#[cfg(feature = "std")]
+extern crate std;
 
 use core::{
     alloc::Layout,
-    any::TypeId,
+    any::{Any, TypeId},
+    error::Error,
     marker::PhantomData,
     mem::MaybeUninit,
     ptr::{DynMetadata, NonNull, Pointee},
 };
 
+pub mod checked;
 pub mod traitcast;
 
+mod arena_dyn;
+mod dyn_debug;
+mod dyn_set;
+mod dyn_tagged;
+mod dyn_typed;
+mod static_dyn;
+
+pub use arena_dyn::{ArenaDyn, DynArena};
+pub use dyn_debug::DynDebug;
+pub use dyn_set::{DynSet, DynViews};
+pub use dyn_tagged::DynTagged;
+pub use dyn_typed::{DynTyped, DynTypedMut};
+pub use static_dyn::StaticDyn;
+
+#[cfg(feature = "std")]
+pub use static_dyn::LazyStaticDyn;
+
+#[cfg(feature = "std")]
+mod interned_dyn_ptr;
+
+#[cfg(feature = "std")]
+pub use interned_dyn_ptr::InternedDynPtr;
+
+#[cfg(feature = "alloc")]
+mod boxed_dyn_ptr;
+#[cfg(feature = "alloc")]
+mod dyn_box;
+#[cfg(feature = "alloc")]
+mod dyn_pin_box;
+#[cfg(feature = "alloc")]
+mod dyn_vec;
+#[cfg(feature = "alloc")]
+mod handle_arena;
+#[cfg(all(feature = "alloc", target_has_atomic = "ptr"))]
+mod revocable_dyn;
+
+#[cfg(all(feature = "alloc", target_has_atomic = "ptr"))]
+pub use boxed_dyn_ptr::AtomicDynPtr;
+#[cfg(feature = "alloc")]
+pub use boxed_dyn_ptr::BoxedDynPtr;
+#[cfg(feature = "alloc")]
+pub use dyn_box::DynBox;
+#[cfg(feature = "alloc")]
+pub use dyn_pin_box::DynPinBox;
+#[cfg(feature = "alloc")]
+pub use dyn_vec::DynVec;
+#[cfg(feature = "alloc")]
+pub use handle_arena::{HandleArena, HandleId};
+#[cfg(all(feature = "alloc", target_has_atomic = "ptr"))]
+pub use revocable_dyn::RevocableDyn;
+
+#[cfg(feature = "debug-validity")]
+mod validity;
+
+#[cfg(feature = "debug-validity")]
+pub use validity::Generation;
+#[cfg(all(feature = "debug-validity", debug_assertions))]
+use validity::GenerationTag;
+
+/// Produces a [`PhantomData`] value for the trait object type of the given
+/// trait, for use with [`Dyn::cast_as`].
+///
+/// `dyn_of!(SomeTrait)` is shorthand for `PhantomData::<dyn SomeTrait>`.
+/// Additional bounds can be included, as in `dyn_of!(SomeTrait + Send)`.
+#[macro_export]
+macro_rules! dyn_of {
+    ($($bounds:tt)+) => {
+        ::core::marker::PhantomData::<dyn $($bounds)+>
+    };
+}
+
+/// Splits a pointer to a trait object into its data pointer and its trait
+/// object metadata.
+///
+/// This is the inverse of [`reconstruct`]. Both are thin wrappers around the
+/// unstable `ptr_metadata` feature of the standard library, which this crate
+/// depends on. Callers can hold and pass around the returned metadata without
+/// enabling that feature themselves, as long as they don't need to write out
+/// the [`DynMetadata`] type by name.
+///
+/// ```
+/// # use core::{fmt::Debug, ptr::NonNull};
+/// let value = 5_u32;
+/// let ptr = NonNull::from(&value as &dyn Debug);
+/// let (thin, metadata) = any_dyn::deconstruct(ptr);
+/// assert_eq!(thin, NonNull::from(&value).cast::<()>());
+/// assert_eq!(any_dyn::reconstruct(thin, metadata), ptr);
+/// ```
+#[inline]
+pub const fn deconstruct<Dyn: TraitObject + ?Sized>(
+    ptr: NonNull<Dyn>,
+) -> (NonNull<()>, DynMetadata<Dyn>) {
+    (ptr.cast::<()>(), core::ptr::metadata(ptr.as_ptr()))
+}
+
+/// Combines a data pointer and trait object metadata into a pointer to a
+/// trait object.
+///
+/// This is the same operation that [`DynPtr::cast`] uses to rebuild a trait
+/// object pointer from its erased parts. The result has the same address and
+/// provenance as `thin`. Refer to [`deconstruct`] for an example.
+///
+/// This is safe because the result is only a raw pointer, but dereferencing it
+/// is valid only if `metadata` describes the object that `thin` points to,
+/// such as when both came from the same call to [`deconstruct`].
+#[inline]
+pub const fn reconstruct<Dyn: TraitObject + ?Sized>(
+    thin: NonNull<()>,
+    metadata: DynMetadata<Dyn>,
+) -> NonNull<Dyn> {
+    NonNull::from_raw_parts(thin, metadata)
+}
+
 /// A shared reference to a trait object for an erased trait tracked only at
 /// runtime.
 ///
@@ -102,193 +249,1567 @@ pub struct Dyn<'a> {
     _phantom: PhantomData<&'a ()>,
 }
 
-impl<'a> Dyn<'a> {
-    /// Creates a [`Dyn`] value that represents the same trait object
-    /// given in `from`, but with the specific trait erased as runtime data
-    /// instead of part of the result type.
+impl<'a> Dyn<'a> {
+    /// Creates a [`Dyn`] value that represents the same trait object
+    /// given in `from`, but with the specific trait erased as runtime data
+    /// instead of part of the result type.
+    ///
+    /// Callers can recover `from` by calling [`Dyn::cast`] with the
+    /// same trait object type.
+    ///
+    /// The identity of the result depends only on the trait object type, and
+    /// not on which concrete type implements the trait. If a trait is
+    /// implemented for a reference or smart pointer type, such as `&Foo` or
+    /// `Box<Foo>`, then erasing a reference to that wrapper produces a
+    /// trait object for the wrapper itself: its address and size are those
+    /// of the wrapper rather than of `Foo`. Because the trait object type
+    /// must be `'static`, an implementation for `&'a Foo` can be erased only
+    /// when `'a` is `'static`.
+    #[inline]
+    #[track_caller]
+    pub const fn new<Dyn: TraitObject + ?Sized + 'static>(from: &'a Dyn) -> Self {
+        let ptr = DynPtr::new(NonNull::from_ref(from));
+        // Safety: We're returning with the same lifetime we were given.
+        unsafe { Self::from_raw(ptr) }
+    }
+
+    /// Returns an iterator that erases each of the trait objects in the given
+    /// slice, producing a [`Dyn`] for each one.
+    ///
+    /// This is for combining homogeneous collections of trait objects into
+    /// heterogeneous collections of [`Dyn`] values, and is the lazy
+    /// equivalent of `Dyn::erase_slice`.
+    ///
+    /// ```
+    /// # use any_dyn::Dyn;
+    /// # use core::fmt::Debug;
+    /// let debugs: &[&dyn Debug] = &[&1, &"two"];
+    /// assert!(Dyn::erase_each(debugs).all(|obj| obj.cast::<dyn Debug>().is_some()));
+    /// ```
+    #[inline]
+    pub fn erase_each<'s, Dyn: TraitObject + ?Sized + 'static>(
+        slice: &'s [&'a Dyn],
+    ) -> impl ExactSizeIterator<Item = Self> + 's
+    where
+        'a: 's,
+    {
+        slice.iter().map(|&obj| Self::new(obj))
+    }
+
+    /// Erases each of the trait objects in the given slice, collecting the
+    /// results into a vector.
+    ///
+    /// This is available only when the `alloc` feature is enabled.
+    #[cfg(feature = "alloc")]
+    #[inline]
+    pub fn erase_slice<Dyn: TraitObject + ?Sized + 'static>(
+        slice: &[&'a Dyn],
+    ) -> alloc::vec::Vec<Self> {
+        Self::erase_each(slice).collect()
+    }
+
+    /// Conjures a [`Dyn`] with an arbitrary lifetime from a [`DynPtr`].
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure that the resulting lifetime is correct for
+    /// the object behind the given pointer.
+    #[inline]
+    pub const unsafe fn from_raw(ptr: DynPtr) -> Self {
+        Self {
+            ptr,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Like [`Dyn::from_raw`], but with the lifetime tied to a borrow of
+    /// `owner`, such as an arena allocator that owns the object.
+    ///
+    /// This packages the common pattern of storing [`DynPtr`] values that
+    /// refer to objects in an arena, and then recovering references that
+    /// cannot outlive the arena. Once constructed, the result can be used
+    /// safely in the same way as any other [`Dyn`]. Arenas that implement
+    /// [`DynArena`] can use [`ArenaDyn`] instead, which needs no `unsafe`.
+    ///
+    /// ```
+    /// # use any_dyn::{Dyn, DynPtr};
+    /// # use core::{cell::RefCell, fmt::Display, ptr::NonNull};
+    /// /// A very simple arena that never frees anything until it's dropped.
+    /// struct Arena {
+    ///     objects: RefCell<Vec<Box<dyn Display>>>,
+    /// }
+    ///
+    /// impl Arena {
+    ///     fn alloc(&self, value: impl Display + 'static) -> DynPtr {
+    ///         let obj: Box<dyn Display> = Box::new(value);
+    ///         let ptr = DynPtr::new(NonNull::from(&*obj));
+    ///         // Moving the box does not move the object it owns.
+    ///         self.objects.borrow_mut().push(obj);
+    ///         ptr
+    ///     }
+    /// }
+    ///
+    /// let arena = Arena { objects: RefCell::new(Vec::new()) };
+    /// let ptrs = [arena.alloc(1), arena.alloc("two")];
+    ///
+    /// // Safety: The objects belong to the arena, which never frees them
+    /// // or gives out mutable access to them while it's borrowed.
+    /// let objs = ptrs.map(|ptr| unsafe { Dyn::from_raw_in(ptr, &arena) });
+    /// let strs = objs.map(|obj| obj.cast::<dyn Display>().unwrap().to_string());
+    /// assert_eq!(strs, ["1", "two"]);
+    /// ```
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure that the object behind the given pointer
+    /// remains valid and is not mutated for as long as `owner` is borrowed.
+    #[inline]
+    pub const unsafe fn from_raw_in<Owner: ?Sized>(ptr: DynPtr, owner: &'a Owner) -> Self {
+        let _ = owner;
+        // Safety: The caller must uphold the same requirements, with the
+        // lifetime of the borrow of owner.
+        unsafe { Self::from_raw(ptr) }
+    }
+
+    /// Cast returns a reference to a trait object of type `Dyn` if and only if
+    /// this [`Dyn`] value was constructed from a trait object of the same
+    /// type.
+    #[inline]
+    pub fn cast<Dyn: TraitObject + ?Sized + 'static>(self) -> Option<&'a Dyn> {
+        self.ptr.cast::<Dyn>().map(|ptr| unsafe {
+            // Safety: DynPtr guarantees that it will only return Some
+            // if the following is safe.
+            ptr.as_ref()
+        })
+    }
+
+    /// Checks that this [`Dyn`] value was constructed from a trait object of
+    /// type `Dyn` and, if so, returns a [`DynTyped`] that can then return
+    /// that trait object without any further runtime checks.
+    #[inline]
+    pub fn typed<Dyn: TraitObject + ?Sized + 'static>(self) -> Option<DynTyped<'a, Dyn>> {
+        if self.dyn_type_id() != DynTypeId::of::<Dyn>() {
+            return None;
+        }
+        // Safety: We just checked that the trait object type matches.
+        Some(unsafe { DynTyped::new_unchecked(self) })
+    }
+
+    /// Creates a [`Dyn`] value representing the given error.
+    ///
+    /// This is a convenience for `Dyn::new::<dyn Error>(e)`, for code that
+    /// routes type-erased errors. Use [`Dyn::cast_error`] to recover the
+    /// error.
+    ///
+    /// ```
+    /// # use any_dyn::Dyn;
+    /// # use core::error::Error;
+    /// let err = "not a number".parse::<u32>().unwrap_err();
+    /// let erased = Dyn::new_error(&err);
+    /// if let Some(err) = erased.cast_error() {
+    ///     println!("failed: {err}");
+    /// }
+    /// # assert!(erased.cast_error().is_some());
+    /// ```
+    #[inline]
+    pub const fn new_error(e: &'a (dyn Error + 'static)) -> Self {
+        Self::new(e)
+    }
+
+    /// Like [`Dyn::new_error`], but for errors that are also [`Send`] and
+    /// [`Sync`], as is common for boxed errors.
+    ///
+    /// The result can be recovered either with [`Dyn::cast_error`], which
+    /// discards the auto traits, or with [`Dyn::cast_error_send_sync`].
+    #[inline]
+    pub const fn new_error_send_sync(e: &'a (dyn Error + Send + Sync + 'static)) -> Self {
+        Self::new(e)
+    }
+
+    /// Returns the error this [`Dyn`] value represents, if it was constructed
+    /// from either a `dyn Error` or a `dyn Error + Send + Sync` trait object.
+    #[inline]
+    pub fn cast_error(self) -> Option<&'a (dyn Error + 'static)> {
+        match self.cast::<dyn Error>() {
+            Some(e) => Some(e),
+            None => self.cast_error_send_sync().map(|e| e as &dyn Error),
+        }
+    }
+
+    /// Returns the error this [`Dyn`] value represents, if it was constructed
+    /// from a `dyn Error + Send + Sync` trait object.
+    #[inline]
+    pub fn cast_error_send_sync(self) -> Option<&'a (dyn Error + Send + Sync + 'static)> {
+        self.cast::<dyn Error + Send + Sync>()
+    }
+
+    /// Like [`Dyn::cast`], but also returns the [`DynTypeId`] of the trait
+    /// object type that matched, for situations where both are needed.
+    #[inline]
+    pub fn cast_with_id<Dyn: TraitObject + ?Sized + 'static>(self) -> Option<(&'a Dyn, DynTypeId)> {
+        self.cast::<Dyn>().map(|obj| (obj, self.dyn_type_id()))
+    }
+
+    /// Like [`Dyn::cast`], but with the trait object type given as a
+    /// [`PhantomData`] argument instead of a type parameter.
+    ///
+    /// This can be more convenient than [`Dyn::cast`] in generated code and
+    /// builder-style method chains. [`dyn_of`] is a shorthand for creating the
+    /// argument.
+    ///
+    /// ```
+    /// # use any_dyn::{Dyn, dyn_of};
+    /// # use core::fmt::Debug;
+    /// let erased = Dyn::new(&5 as &dyn Debug);
+    /// assert!(erased.cast_as(dyn_of!(Debug)).is_some());
+    /// ```
+    #[inline]
+    pub fn cast_as<Dyn: TraitObject + ?Sized + 'static>(
+        self,
+        _: PhantomData<Dyn>,
+    ) -> Option<&'a Dyn> {
+        self.cast::<Dyn>()
+    }
+
+    /// Casts to the trait object type `Sub` and then, if successful, uses `f`
+    /// to convert the result to the trait object type `Super`, returning a
+    /// new [`Dyn`] for that trait object type.
+    ///
+    /// This is intended for re-erasing a value as one of its supertraits, so
+    /// that `f` will typically just be a trait upcasting coercion.
+    ///
+    /// ```
+    /// # use any_dyn::Dyn;
+    /// # use core::fmt::Debug;
+    /// trait Named: Debug {
+    ///     fn name(&self) -> &str;
+    /// }
+    /// # #[derive(Debug)]
+    /// # struct Thing;
+    /// # impl Named for Thing { fn name(&self) -> &str { "thing" } }
+    ///
+    /// let erased = Dyn::new(&Thing as &dyn Named);
+    /// let debug = erased.recast(|named: &dyn Named| named as &dyn Debug).unwrap();
+    /// assert!(debug.cast::<dyn Debug>().is_some());
+    /// assert!(debug.cast::<dyn Named>().is_none());
+    /// ```
+    ///
+    /// This can also change which auto traits are part of the trait object
+    /// type, because `f` can be any function that the compiler accepts.
+    /// Removing auto traits is a normal coercion, such as from
+    /// `&(dyn Debug + Send)` to `&dyn Debug`. Adding them is possible only when
+    /// `f` can prove that the object has them, such as by downcasting to a
+    /// concrete type known to implement them:
+    ///
+    /// ```
+    /// # use any_dyn::Dyn;
+    /// # use core::{any::Any, fmt::Debug};
+    /// let value = 5_u32;
+    /// let erased = Dyn::new(&value as &dyn Any);
+    /// let sendable = erased
+    ///     .recast(|any: &dyn Any| any.downcast_ref::<u32>().unwrap() as &(dyn Debug + Send))
+    ///     .unwrap();
+    /// assert!(sendable.cast::<dyn Debug + Send>().is_some());
+    /// ```
+    #[inline]
+    #[track_caller]
+    pub fn recast<Sub, Super>(self, f: impl FnOnce(&'a Sub) -> &'a Super) -> Option<Dyn<'a>>
+    where
+        Sub: TraitObject + ?Sized + 'static,
+        Super: TraitObject + ?Sized + 'static,
+    {
+        self.cast::<Sub>().map(|sub| Dyn::new(f(sub)))
+    }
+
+    /// Like [`Dyn::cast`], but panics if the trait object type doesn't match.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this [`Dyn`] value was not constructed from a trait object
+    /// of type `Dyn`. The panic is reported at the caller's location.
+    #[inline]
+    #[track_caller]
+    pub fn expect_cast<Dyn: TraitObject + ?Sized + 'static>(self) -> &'a Dyn {
+        let ptr = self.ptr.expect_cast::<Dyn>();
+        // Safety: We're returning with the lifetime this value was created with.
+        unsafe { ptr.as_ref() }
+    }
+
+    /// Returns a reference to the concrete type `T` if and only if this
+    /// [`Dyn`] value was constructed from a `dyn Any` trait object whose
+    /// underlying value has type `T`.
+    ///
+    /// ```
+    /// # use any_dyn::Dyn;
+    /// # use core::any::Any;
+    /// let value = 5_u32;
+    /// let erased = Dyn::new(&value as &dyn Any);
+    /// assert_eq!(erased.downcast_concrete::<u32>(), Some(&5));
+    /// assert_eq!(erased.downcast_concrete::<u64>(), None);
+    /// ```
+    #[inline]
+    pub fn downcast_concrete<T: Any>(self) -> Option<&'a T> {
+        self.cast::<dyn Any>()?.downcast_ref::<T>()
+    }
+
+    /// Like [`Dyn::downcast_concrete`], but for a [`Dyn`] value constructed
+    /// from a `dyn Any + Send + Sync` trait object.
+    ///
+    /// `dyn Any + Send + Sync` is a different trait object type than
+    /// `dyn Any`, so each of these methods succeeds only for a [`Dyn`]
+    /// constructed from its own trait object type.
+    #[inline]
+    pub fn downcast_concrete_send_sync<T: Any + Send + Sync>(self) -> Option<&'a T> {
+        self.cast::<dyn Any + Send + Sync>()?.downcast_ref::<T>()
+    }
+
+    /// Returns the [`TypeId`] of the concrete type behind this trait object
+    /// if it was constructed from a `dyn Any` or `dyn Any + Send + Sync`
+    /// trait object, or [`None`] otherwise.
+    ///
+    /// This is useful for diagnostics, such as for logging which type an
+    /// erased handle refers to, without needing to try downcasting to each
+    /// possible type in turn.
+    ///
+    /// ```
+    /// # use any_dyn::Dyn;
+    /// # use core::any::{Any, TypeId};
+    /// # use core::fmt::Debug;
+    /// let value = 5_u32;
+    /// let erased = Dyn::new(&value as &dyn Any);
+    /// assert_eq!(erased.concrete_type_id(), Some(TypeId::of::<u32>()));
+    ///
+    /// // The concrete type is unknown for other trait object types.
+    /// let erased = Dyn::new(&value as &dyn Debug);
+    /// assert_eq!(erased.concrete_type_id(), None);
+    /// ```
+    #[inline]
+    pub fn concrete_type_id(self) -> Option<TypeId> {
+        if let Some(any) = self.cast::<dyn Any>() {
+            return Some(any.type_id());
+        }
+        self.cast::<dyn Any + Send + Sync>()
+            .map(|any| (any as &dyn Any).type_id())
+    }
+
+    /// Returns the underlying [`DynPtr`] for this trait object reference.
+    ///
+    /// The result can outlive this handle, so it does not keep any generation
+    /// associated with this handle by `with_generation`.
+    #[inline]
+    pub const fn as_ptr(self) -> DynPtr {
+        self.ptr.without_borrowed_generation()
+    }
+
+    /// Associates this handle with the current generation of `generation`,
+    /// so that later casts can detect if the generation has since advanced.
+    ///
+    /// This is like [`DynPtr::with_generation`], except that `generation`
+    /// needs to be borrowed only for as long as this handle, and so it can
+    /// belong to the container that owns the object. Copies of the handle
+    /// keep the association, but [`Dyn::as_ptr`] does not, because its
+    /// result could outlive the borrow.
+    ///
+    /// As with [`DynPtr::with_generation`], the association is recorded only
+    /// when debug assertions are enabled.
+    ///
+    /// This is available only when the `debug-validity` feature is enabled.
+    #[cfg(feature = "debug-validity")]
+    #[inline]
+    pub fn with_generation(self, generation: &'a Generation) -> Self {
+        Self {
+            // Safety: This handle, and therefore the tag, cannot outlive
+            // `'a`, and the tag is removed whenever the pointer leaves it.
+            ptr: unsafe { self.ptr.with_borrowed_generation(generation) },
+            ..self
+        }
+    }
+
+    /// Returns the [`DynTypeId`] of the trait object type this value was
+    /// constructed from.
+    #[inline]
+    pub const fn dyn_type_id(self) -> DynTypeId {
+        self.ptr.dyn_type_id()
+    }
+
+    /// Returns `true` if `self` and `other` refer to the same object viewed
+    /// through the same trait.
+    ///
+    /// Refer to [`DynPtr::ptr_eq`] for the details of this comparison.
+    #[inline]
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        self.ptr.ptr_eq(&other.ptr)
+    }
+
+    /// Returns `true` if `self` and `other` refer to the same object,
+    /// regardless of which traits they are viewed through.
+    ///
+    /// Refer to [`DynPtr::same_object`] for the details of this comparison.
+    #[inline]
+    pub fn same_object(&self, other: &Self) -> bool {
+        self.ptr.same_object(&other.ptr)
+    }
+}
+
+/// [`Dyn`] values compare by identity, as described for [`DynPtr`]'s
+/// implementation of [`PartialEq`].
+impl PartialEq for Dyn<'_> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.ptr_eq(other)
+    }
+}
+
+impl Eq for Dyn<'_> {}
+
+impl core::hash::Hash for Dyn<'_> {
+    #[inline]
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        self.ptr.hash(state);
+    }
+}
+
+/// A mutable reference to a trait object for an erased trait tracked only at
+/// runtime.
+///
+/// This is essentially the same as [`Dyn`] except that it represents a
+/// mutable reference instead of a shared reference.
+///
+/// In other words, this is like `&'a mut dyn Trait`, but with `Trait` tracked
+/// dynamically instead of statically,
+#[derive(Debug, Clone, Copy)]
+pub struct DynMut<'a> {
+    ptr: DynPtr,
+    _phantom: PhantomData<&'a mut ()>,
+}
+
+impl<'a> DynMut<'a> {
+    /// Creates a [`DynMut`] value that represents the same trait object
+    /// given in `from`, but with the specific trait erased as runtime data
+    /// instead of part of the result type.
+    ///
+    /// Callers can recover `from` by calling [`DynMut::cast`] with the
+    /// same trait object type.
+    #[inline]
+    #[track_caller]
+    pub const fn new<Dyn: TraitObject + ?Sized + 'static>(from: &'a mut Dyn) -> Self {
+        let ptr = DynPtr::new(NonNull::from_mut(from));
+        // Safety: We're returning with the same lifetime we were given.
+        unsafe { Self::from_raw(ptr) }
+    }
+
+    /// Conjures a [`DynMut`] with an arbitrary lifetime from a [`DynPtr`].
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure that the resulting lifetime is correct for
+    /// the object behind the given pointer.
+    #[inline]
+    pub const unsafe fn from_raw(ptr: DynPtr) -> Self {
+        Self {
+            ptr,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Cast returns a reference to a trait object of type `Dyn` if and only if
+    /// this [`DynMut`] value was constructed from a trait object of the same
+    /// type.
+    #[inline]
+    pub fn cast<Dyn: TraitObject + ?Sized + 'static>(self) -> Option<&'a mut Dyn> {
+        self.ptr.cast::<Dyn>().map(|mut ptr| unsafe {
+            // Safety: DynPtr guarantees that it will only return Some
+            // if the following is safe.
+            ptr.as_mut()
+        })
+    }
+
+    /// Checks that this [`DynMut`] value was constructed from a trait object
+    /// of type `Dyn` and, if so, returns a [`DynTypedMut`] that can then
+    /// return that trait object without any further runtime checks.
+    #[inline]
+    pub fn typed<Dyn: TraitObject + ?Sized + 'static>(self) -> Option<DynTypedMut<'a, Dyn>> {
+        if self.dyn_type_id() != DynTypeId::of::<Dyn>() {
+            return None;
+        }
+        // Safety: We just checked that the trait object type matches.
+        Some(unsafe { DynTypedMut::new_unchecked(self) })
+    }
+
+    /// Creates a [`DynMut`] value representing a formatting sink.
+    ///
+    /// This is a convenience for `DynMut::new::<dyn core::fmt::Write>(w)`, for
+    /// code that routes type-erased output destinations. Use
+    /// [`DynMut::cast_writer`] to recover the sink.
+    ///
+    /// ```
+    /// # use any_dyn::DynMut;
+    /// let mut out = String::new();
+    /// let erased = DynMut::new_writer(&mut out);
+    /// if let Some(w) = erased.cast_writer() {
+    ///     write!(w, "{} + {} = {}", 1, 2, 1 + 2).unwrap();
+    /// }
+    /// assert_eq!(out, "1 + 2 = 3");
+    /// ```
+    #[inline]
+    pub const fn new_writer(w: &'a mut (dyn core::fmt::Write + 'static)) -> Self {
+        Self::new(w)
+    }
+
+    /// Returns the formatting sink this [`DynMut`] value represents, if it was
+    /// constructed from a `dyn core::fmt::Write` trait object.
+    #[inline]
+    pub fn cast_writer(self) -> Option<&'a mut (dyn core::fmt::Write + 'static)> {
+        self.cast::<dyn core::fmt::Write>()
+    }
+
+    /// Like [`DynMut::cast`], but borrows this handle instead of consuming it,
+    /// so the result's lifetime is limited to that borrow.
+    ///
+    /// Because [`DynMut`] is [`Copy`], calling [`DynMut::cast`] more than once
+    /// on the same handle can produce multiple mutable references to the same
+    /// object that are all live at once. With this method the borrow checker
+    /// prevents that, because each result keeps `self` mutably borrowed for as
+    /// long as it's in use.
+    ///
+    /// ```compile_fail,E0499
+    /// # use any_dyn::DynMut;
+    /// # use core::fmt::Write;
+    /// let mut s = String::new();
+    /// let mut erased = DynMut::new(&mut s as &mut dyn Write);
+    /// let a = erased.cast_exclusive::<dyn Write>().unwrap();
+    /// let b = erased.cast_exclusive::<dyn Write>().unwrap();
+    /// a.write_str("a").unwrap();
+    /// b.write_str("b").unwrap();
+    /// ```
+    #[inline]
+    pub fn cast_exclusive<Dyn: TraitObject + ?Sized + 'static>(&mut self) -> Option<&mut Dyn> {
+        self.ptr.cast::<Dyn>().map(|mut ptr| unsafe {
+            // Safety: DynPtr guarantees that it will only return Some
+            // if the following is safe, and the result borrows self.
+            ptr.as_mut()
+        })
+    }
+
+    /// Like [`DynMut::cast`], but also returns the [`DynTypeId`] of the trait
+    /// object type that matched, for situations where both are needed.
+    #[inline]
+    pub fn cast_with_id<Dyn: TraitObject + ?Sized + 'static>(
+        self,
+    ) -> Option<(&'a mut Dyn, DynTypeId)> {
+        self.cast::<Dyn>().map(|obj| (obj, self.dyn_type_id()))
+    }
+
+    /// Like [`DynMut::cast`], but panics if the trait object type doesn't
+    /// match.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this [`DynMut`] value was not constructed from a trait object
+    /// of type `Dyn`. The panic is reported at the caller's location.
+    #[inline]
+    #[track_caller]
+    pub fn expect_cast<Dyn: TraitObject + ?Sized + 'static>(self) -> &'a mut Dyn {
+        let mut ptr = self.ptr.expect_cast::<Dyn>();
+        // Safety: We're returning with the lifetime this value was created with.
+        unsafe { ptr.as_mut() }
+    }
+
+    /// Returns a mutable reference to the concrete type `T` if and only if
+    /// this [`DynMut`] value was constructed from a `dyn Any` trait object
+    /// whose underlying value has type `T`.
+    #[inline]
+    pub fn downcast_concrete<T: Any>(self) -> Option<&'a mut T> {
+        self.cast::<dyn Any>()?.downcast_mut::<T>()
+    }
+
+    /// Like [`DynMut::downcast_concrete`], but for a [`DynMut`] value
+    /// constructed from a `dyn Any + Send + Sync` trait object.
+    #[inline]
+    pub fn downcast_concrete_send_sync<T: Any + Send + Sync>(self) -> Option<&'a mut T> {
+        self.cast::<dyn Any + Send + Sync>()?.downcast_mut::<T>()
+    }
+
+    /// Returns the underlying [`DynPtr`] for this trait object reference.
+    ///
+    /// The result can outlive this handle, so it does not keep any generation
+    /// associated with this handle by `with_generation`.
+    #[inline]
+    pub const fn as_ptr(self) -> DynPtr {
+        self.ptr.without_borrowed_generation()
+    }
+
+    /// Associates this handle with the current generation of `generation`,
+    /// so that later casts can detect if the generation has since advanced.
+    ///
+    /// This is like [`DynPtr::with_generation`], except that `generation`
+    /// needs to be borrowed only for as long as this handle, and so it can
+    /// belong to the container that owns the object. Copies of the handle
+    /// keep the association, but [`DynMut::as_ptr`] does not, because its
+    /// result could outlive the borrow.
+    ///
+    /// As with [`DynPtr::with_generation`], the association is recorded only
+    /// when debug assertions are enabled.
+    ///
+    /// This is available only when the `debug-validity` feature is enabled.
+    #[cfg(feature = "debug-validity")]
+    #[inline]
+    pub fn with_generation(self, generation: &'a Generation) -> Self {
+        Self {
+            // Safety: This handle, and therefore the tag, cannot outlive
+            // `'a`, and the tag is removed whenever the pointer leaves it.
+            ptr: unsafe { self.ptr.with_borrowed_generation(generation) },
+            ..self
+        }
+    }
+
+    /// Returns the [`DynTypeId`] of the trait object type this value was
+    /// constructed from.
+    #[inline]
+    pub const fn dyn_type_id(self) -> DynTypeId {
+        self.ptr.dyn_type_id()
+    }
+
+    /// Returns `true` if `self` and `other` refer to the same object viewed
+    /// through the same trait.
+    ///
+    /// Refer to [`DynPtr::ptr_eq`] for the details of this comparison.
+    #[inline]
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        self.ptr.ptr_eq(&other.ptr)
+    }
+
+    /// Returns `true` if `self` and `other` refer to the same object,
+    /// regardless of which traits they are viewed through.
+    ///
+    /// Refer to [`DynPtr::same_object`] for the details of this comparison.
+    #[inline]
+    pub fn same_object(&self, other: &Self) -> bool {
+        self.ptr.same_object(&other.ptr)
+    }
+
+    /// Casts two [`DynMut`] values to the same trait object type at once,
+    /// returning both mutable references if and only if both casts succeed,
+    /// the two handles are not for the [same object](DynMut::same_object),
+    /// and the two objects do not overlap in memory.
+    ///
+    /// This allows using two type-erased mutable references together, such
+    /// as to swap or compare their contents, while ensuring that the two
+    /// results cannot alias each other.
+    ///
+    /// ```
+    /// # use any_dyn::DynMut;
+    /// trait Counter {
+    ///     fn count(&mut self) -> &mut usize;
+    /// }
+    ///
+    /// struct ExampleCounter(usize);
+    ///
+    /// impl Counter for ExampleCounter {
+    ///     fn count(&mut self) -> &mut usize {
+    ///         &mut self.0
+    ///     }
+    /// }
+    ///
+    /// let mut a = ExampleCounter(1);
+    /// let mut b = ExampleCounter(2);
+    /// let (a, b) = DynMut::cast_disjoint_mut::<dyn Counter>(
+    ///     DynMut::new(&mut a as &mut dyn Counter),
+    ///     DynMut::new(&mut b as &mut dyn Counter),
+    /// )
+    /// .unwrap();
+    /// core::mem::swap(a.count(), b.count());
+    /// assert_eq!((*a.count(), *b.count()), (2, 1));
+    /// ```
+    pub fn cast_disjoint_mut<Dyn: TraitObject + ?Sized + 'static>(
+        a: Self,
+        b: Self,
+    ) -> Option<(&'a mut Dyn, &'a mut Dyn)> {
+        let sizes = (a.ptr.value_size::<Dyn>()?, b.ptr.value_size::<Dyn>()?);
+        // Zero-sized objects never overlap by size alone, so handles to the
+        // same object must be rejected separately.
+        if a.ptr.same_object(&b.ptr) || a.ptr.overlaps(&b.ptr, sizes) {
+            return None;
+        }
+        Some((a.cast::<Dyn>()?, b.cast::<Dyn>()?))
+    }
+
+    /// Swaps the objects that `self` and `other` refer to, byte-for-byte, if
+    /// both were constructed from trait objects of type `Dyn` and they have
+    /// the same layout.
+    ///
+    /// Returns `true` if the objects were swapped, or if both handles refer
+    /// to the same object and so there is nothing to do. Returns `false`
+    /// without changing anything if either trait object type is not `Dyn`, if
+    /// the layouts differ, or if the objects partially overlap.
+    ///
+    /// This supports reorganizing tables of objects in place without knowing
+    /// their concrete types.
+    ///
+    /// # Safety
+    ///
+    /// The trait object type and layout are checked, but neither can confirm
+    /// that the two objects have the same _concrete_ type. The caller must
+    /// ensure that they do, because otherwise each object's memory would be
+    /// reinterpreted as the other object's type.
+    ///
+    /// The objects must also not depend on their own addresses, such as by
+    /// being pinned or containing pointers to themselves.
+    ///
+    /// ```
+    /// # use any_dyn::DynMut;
+    /// # use core::fmt::Debug;
+    /// let mut a = String::from("a");
+    /// let mut b = String::from("b");
+    /// let mut a_erased = DynMut::new(&mut a as &mut dyn Debug);
+    /// let mut b_erased = DynMut::new(&mut b as &mut dyn Debug);
+    /// // Safety: Both objects are of type String.
+    /// assert!(unsafe { a_erased.swap_if_same_trait::<dyn Debug>(&mut b_erased) });
+    /// assert_eq!((a.as_str(), b.as_str()), ("b", "a"));
+    /// ```
+    pub unsafe fn swap_if_same_trait<Dyn: TraitObject + ?Sized + 'static>(
+        &mut self,
+        other: &mut DynMut<'_>,
+    ) -> bool {
+        let (Some(layout), Some(other_layout)) =
+            (self.ptr.layout_as::<Dyn>(), other.ptr.layout_as::<Dyn>())
+        else {
+            return false;
+        };
+        if layout != other_layout {
+            return false;
+        }
+        if self.ptr.thin == other.ptr.thin {
+            return true;
+        }
+        if self
+            .ptr
+            .overlaps(&other.ptr, (layout.size(), layout.size()))
+        {
+            return false;
+        }
+        // Safety: Both pointers are valid for reads and writes of the size
+        // reported by their metadata, the regions don't overlap, and the
+        // caller guarantees that the objects have the same concrete type.
+        unsafe {
+            core::ptr::swap_nonoverlapping(
+                self.ptr.thin.as_ptr() as *mut u8,
+                other.ptr.thin.as_ptr() as *mut u8,
+                layout.size(),
+            );
+        }
+        true
+    }
+
+    /// Calls `f` with a shared [`Dyn`] view of the same trait object, and
+    /// then returns whatever `f` returned.
+    ///
+    /// The shared view borrows `self` for the duration of the call, so the
+    /// object cannot be mutated through this [`DynMut`] until `f` returns.
+    ///
+    /// ```
+    /// # use any_dyn::DynMut;
+    /// trait Counter {
+    ///     fn get(&self) -> usize;
+    ///     fn increment(&mut self);
+    /// }
+    ///
+    /// struct ExampleCounter(usize);
+    ///
+    /// impl Counter for ExampleCounter {
+    ///     fn get(&self) -> usize {
+    ///         self.0
+    ///     }
+    ///     fn increment(&mut self) {
+    ///         self.0 += 1;
+    ///     }
+    /// }
+    ///
+    /// let mut counter = ExampleCounter(1);
+    /// let mut erased = DynMut::new(&mut counter as &mut dyn Counter);
+    /// let before = erased.with_shared(|shared| shared.cast::<dyn Counter>().unwrap().get());
+    /// erased.cast::<dyn Counter>().unwrap().increment();
+    /// assert_eq!(before, 1);
+    /// assert_eq!(counter.get(), 2);
+    /// ```
+    #[inline]
+    pub fn with_shared<R>(&mut self, f: impl FnOnce(Dyn<'_>) -> R) -> R {
+        // Safety: The shared view cannot outlive the borrow of self, and
+        // so cannot coexist with any mutable use of this handle.
+        let shared = unsafe { Dyn::from_raw(self.ptr) };
+        f(shared)
+    }
+
+    /// Converts this handle into a [`StaticDyn`], ending the phase where the
+    /// object can be mutated and beginning a phase where it's shared
+    /// immutably, potentially between threads, for the rest of the program.
+    ///
+    /// This is intended for staged initialization, where an object is built
+    /// up through a [`DynMut`] and then published for shared use.
+    ///
+    /// ```
+    /// # use any_dyn::{DynMut, StaticDyn};
+    /// # use std::sync::OnceLock;
+    /// trait Settings: Sync {
+    ///     fn set_verbose(&mut self);
+    ///     fn verbose(&self) -> bool;
+    /// }
+    /// # struct Defaults(bool);
+    /// # impl Settings for Defaults {
+    /// #     fn set_verbose(&mut self) { self.0 = true; }
+    /// #     fn verbose(&self) -> bool { self.0 }
+    /// # }
+    ///
+    /// static SETTINGS: OnceLock<StaticDyn> = OnceLock::new();
+    ///
+    /// let settings: &'static mut dyn Settings = Box::leak(Box::new(Defaults(false)));
+    /// let erased = DynMut::new(settings);
+    /// erased.cast::<dyn Settings>().unwrap().set_verbose();
+    ///
+    /// // Safety: The object is leaked, `dyn Settings` is Sync, and `erased`
+    /// // is not used again.
+    /// SETTINGS.set(unsafe { erased.freeze() }).unwrap();
+    /// let settings = SETTINGS.get().unwrap();
+    /// assert!(settings.cast::<dyn Settings>().unwrap().verbose());
+    /// ```
+    ///
+    /// # Safety
+    ///
+    /// This fabricates both a `'static` lifetime and thread-safety, neither
+    /// of which can be checked. The caller must ensure all of the following:
+    ///
+    /// - The object lives for the rest of the program, such as because it was
+    ///   leaked or is a `static` item.
+    /// - The object is never mutated again, whether through this handle, a
+    ///   copy of it, or any other reference, except through interior
+    ///   mutability that is itself thread-safe.
+    /// - The trait object type this handle was constructed from is [`Sync`],
+    ///   as is required by [`StaticDyn::new`].
+    #[inline]
+    pub const unsafe fn freeze(self) -> StaticDyn {
+        // Safety: The caller must uphold the requirements above.
+        unsafe { StaticDyn::from_raw(self.ptr.without_borrowed_generation()) }
+    }
+}
+
+/// [`DynMut`] values compare by identity, as described for [`DynPtr`]'s
+/// implementation of [`PartialEq`]. Two handles to distinct objects are
+/// never equal, even if the objects themselves would compare as equal.
+impl PartialEq for DynMut<'_> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.ptr_eq(other)
+    }
+}
+
+impl Eq for DynMut<'_> {}
+
+/// A non-null raw pointer to a trait object for an arbitrary trait decided at
+/// runtime.
+///
+/// A value of this type is similar to a `NonNull<dyn Trait>`, but with the
+/// specific trait erased so it can vary at runtime.
+///
+/// This is the raw pointer version of [`Dyn`] and [`DynMut`], which
+/// therefore does not track any lifetimes. Those other two types are wrappers
+/// around this which track the lifetime and mutability of the underlying
+/// object.
+///
+/// # Constant evaluation
+///
+/// [`DynPtr::new`], [`Dyn::new`], [`DynMut::new`], and [`DynTypeId::of`] are
+/// all `const fn`, so type-erased trait objects for `'static` implementers
+/// can be built at compile time:
+///
+/// ```
+/// # use any_dyn::{Dyn, DynTypeId};
+/// trait WithMessage {
+///     fn message(&self) -> &'static str;
+/// }
+///
+/// struct HelloWorld;
+///
+/// impl WithMessage for HelloWorld {
+///     fn message(&self) -> &'static str {
+///         "Hello, world!"
+///     }
+/// }
+///
+/// const HELLO: Dyn<'static> = Dyn::new(&HelloWorld as &dyn WithMessage);
+/// const SUPPORTED: [DynTypeId; 1] = [DynTypeId::of::<dyn WithMessage>()];
+///
+/// assert_eq!(HELLO.cast::<dyn WithMessage>().unwrap().message(), "Hello, world!");
+/// assert_eq!(SUPPORTED[0], DynTypeId::of::<dyn WithMessage>());
+/// ```
+///
+/// Casting is not currently possible in `const` contexts, because comparing
+/// [`TypeId`] values is not yet allowed there. For the same reason,
+/// [`DynTypeId`] values can be built at compile time but can only be compared
+/// at runtime.
+///
+/// None of these types are [`Sync`], so they can be used in `const` items but
+/// not directly in `static` items. Use [`StaticDyn`] for trait objects that
+/// need to be stored in `static` items.
+///
+/// # Validity
+///
+/// [`DynPtr`] is `Copy` and does not track the lifetime of the object it
+/// refers to, so moving or dropping that object silently leaves any existing
+/// [`DynPtr`] values dangling. Casting a dangling [`DynPtr`] still succeeds,
+/// but the resulting pointer must not be dereferenced.
+///
+/// The `debug-validity` feature offers `DynPtr::with_generation` as a
+/// debugging aid for detecting this mistake in containers that move objects
+/// around.
+///
+/// None of the types in this crate ever drop the objects they refer to, so
+/// they can also refer to objects inside [`ManuallyDrop`](core::mem::ManuallyDrop)
+/// or other wrappers that manage destruction themselves, such as the slots of
+/// an arena or slab allocator.
+#[derive(Debug, Clone, Copy)]
+pub struct DynPtr {
+    thin: NonNull<()>,
+    metadata: ErasedMetadata,
+    type_id: TypeId,
+    #[cfg(feature = "debug-diagnostics")]
+    created_at: &'static core::panic::Location<'static>,
+    #[cfg(all(feature = "debug-validity", debug_assertions))]
+    generation: Option<GenerationTag>,
+}
+
+impl DynPtr {
+    /// Creates a [`DynPtr`] value that represents the same trait object
+    /// given in `from`, but with the specific trait erased as runtime data
+    /// instead of part of the result type.
+    ///
+    /// Callers can recover `from` by calling [`DynPtr::cast`] with the
+    /// same trait object type.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the metadata for `Dyn` does not have the same layout as
+    /// the metadata for other trait object types. Refer to the crate-level
+    /// documentation for more information. The panic is reported at the
+    /// caller's location.
+    ///
+    /// `from` is not otherwise validated during construction, but when debug
+    /// assertions are enabled [`DynPtr::cast`] panics if the pointer is not
+    /// aligned as required by the metadata.
+    #[track_caller]
+    pub const fn new<Dyn: TraitObject + ?Sized + 'static>(from: NonNull<Dyn>) -> Self {
+        // The following is to make it more likely that we'll notice quickly
+        // if the implementation detail we're relying on changes in a future
+        // version of Rust. Note that we're only depending on equal layout and
+        // not on identical representation, because we never actually try to
+        // access the target data as the erased type.
+        //
+        assert!(
+            Self::layout_assumption_holds::<Dyn>(),
+            "DynMetadata types no longer have fixed layout regardless of type parameter",
+        );
+
+        let thin = from.cast::<()>();
+        let metadata = ErasedMetadata::erase(core::ptr::metadata(from.as_ptr()));
+        let type_id = core::any::TypeId::of::<Dyn>();
+
+        Self {
+            thin,
+            metadata,
+            type_id,
+            #[cfg(feature = "debug-diagnostics")]
+            created_at: core::panic::Location::caller(),
+            #[cfg(all(feature = "debug-validity", debug_assertions))]
+            generation: None,
+        }
+    }
+
+    /// Returns `true` if the trait object metadata for `Dyn` has the same
+    /// layout as the metadata for other trait object types, as described in
+    /// the crate-level documentation.
+    ///
+    /// [`DynPtr::new`] panics if this returns `false` for its type parameter,
+    /// and so do [`Dyn::new`] and [`DynMut::new`], which are built on it.
+    /// Defensive code can call this first and use some other strategy for
+    /// any trait object types where it returns `false`, without giving up on
+    /// erasure for all other traits.
+    ///
+    /// [`checked::verify_layout_assumption`] is equivalent, and
+    /// [`checked::try_new`] and its siblings use this check to avoid
+    /// panicking.
+    ///
+    /// ```
+    /// # use any_dyn::DynPtr;
+    /// # use core::fmt::Debug;
+    /// assert!(DynPtr::layout_assumption_holds::<dyn Debug>());
+    /// ```
+    #[inline]
+    pub const fn layout_assumption_holds<Dyn: TraitObject + ?Sized + 'static>() -> bool {
+        // `Layout`'s `PartialEq` implementation is not usable in a const fn,
+        // so we compare the size and alignment separately.
+        let dyn_layout = const { Layout::new::<DynMetadata<Dyn>>() };
+        let erased_layout = const { Layout::new::<DynMetadata<()>>() };
+        dyn_layout.size() == erased_layout.size() && dyn_layout.align() == erased_layout.align()
+    }
+
+    /// Returns the index of the first element of `ids` that matches the trait
+    /// object type this pointer was constructed from, if any.
+    ///
+    /// This is a data-driven alternative to
+    /// [`match_dyn_type_id`](traitcast::match_dyn_type_id) for a set of
+    /// traits whose size is known at compile time. Combined with an array of
+    /// handler functions of the same length, it allows dispatching by index
+    /// without any allocation.
+    ///
+    /// ```
+    /// # use any_dyn::{DynPtr, DynTypeId};
+    /// # use core::{fmt::{Debug, Display}, ptr::NonNull};
+    /// const IDS: [DynTypeId; 2] = [DynTypeId::of::<dyn Display>(), DynTypeId::of::<dyn Debug>()];
+    /// const HANDLERS: [fn(DynPtr) -> &'static str; 2] = [|_| "display", |_| "debug"];
+    ///
+    /// let ptr = DynPtr::new(NonNull::from(&5 as &dyn Debug));
+    /// let handled = ptr.cast_first_of(IDS).map(|i| HANDLERS[i](ptr));
+    /// assert_eq!(handled, Some("debug"));
+    /// ```
+    #[inline]
+    pub fn cast_first_of<const N: usize>(&self, ids: [DynTypeId; N]) -> Option<usize> {
+        let type_id = self.dyn_type_id();
+        ids.iter().position(|&id| id == type_id)
+    }
+
+    /// Cast returns a pointer to a trait object of type `Dyn` if and only if
+    /// this [`DynPtr`] value was constructed from a trait object of the same
+    /// type.
+    ///
+    /// Whether the cast succeeds is decided by a single comparison of
+    /// [`TypeId`] values, so a failed cast costs no more than a successful
+    /// one and there's little to gain from caching the results of casts.
+    /// The benchmarks in `benches/cast.rs` measure this directly.
+    ///
+    /// The returned pointer is rebuilt from the same data pointer that was
+    /// given to [`DynPtr::new`], using [`reconstruct`], and
+    /// so it has the same address and provenance as the original. It's
+    /// therefore valid for exactly the same accesses as the original pointer
+    /// under the strict provenance rules.
+    #[inline]
+    pub fn cast<Dyn: TraitObject + ?Sized + 'static>(&self) -> Option<NonNull<Dyn>> {
+        #[cfg(all(feature = "debug-validity", debug_assertions))]
+        if let Some(tag) = self.generation {
+            tag.check();
+        }
+        if core::any::TypeId::of::<Dyn>() != self.type_id {
+            return None;
+        }
+        // Safety: If this object was constructed correctly then our erased
+        // metadata is for the requested trait object type.
+        let metadata = unsafe { self.metadata.restore::<Dyn>() };
+        // DynPtr::new can't check this itself because it's a const fn and
+        // the metadata accessors are not, so we check it here instead.
+        debug_assert!(
+            self.thin
+                .as_ptr()
+                .addr()
+                .is_multiple_of(metadata.align_of()),
+            "DynPtr refers to a misaligned object",
+        );
+        Some(reconstruct(self.thin, metadata))
+    }
+
+    /// Returns a `dyn Any` raw pointer to the object if and only if this
+    /// [`DynPtr`] value was constructed from a `dyn Any` trait object.
+    ///
+    /// This is a convenience for passing erased objects to code that works
+    /// with raw `dyn Any` pointers, and is equivalent to
+    /// `self.cast::<dyn Any>()` followed by [`NonNull::as_ptr`].
+    ///
+    /// ```
+    /// # use any_dyn::DynPtr;
+    /// # use core::{any::Any, ptr::NonNull};
+    /// let value = 5_i32;
+    /// let ptr = DynPtr::new(NonNull::from(&value as &dyn Any));
+    /// let any = ptr.as_any_ptr().unwrap();
+    /// assert_eq!(unsafe { &*any }.downcast_ref::<i32>(), Some(&5));
+    /// ```
+    #[inline]
+    pub fn as_any_ptr(&self) -> Option<*const dyn Any> {
+        self.as_any_mut_ptr().map(|ptr| ptr as *const dyn Any)
+    }
+
+    /// Returns a mutable `dyn Any` raw pointer to the object if and only if
+    /// this [`DynPtr`] value was constructed from a `dyn Any` trait object.
+    ///
+    /// Writing through the result is valid only if the [`DynPtr`] was
+    /// constructed from a pointer that permits writes.
+    #[inline]
+    pub fn as_any_mut_ptr(&self) -> Option<*mut dyn Any> {
+        self.cast::<dyn Any>().map(NonNull::as_ptr)
+    }
+
+    /// Runs the destructor of the object this pointer refers to if and only if
+    /// it was constructed from a trait object of type `Dyn`, without needing
+    /// to know the object's concrete type.
+    ///
+    /// Returns `false` without doing anything if the trait object type does
+    /// not match.
+    ///
+    /// This uses the drop glue recorded in the trait object metadata, in the
+    /// same way as calling [`core::ptr::drop_in_place`] with the original
+    /// trait object pointer. It does not free the memory the object occupies,
+    /// which remains the caller's responsibility. This is intended for
+    /// implementing object pools and similar containers that own objects of
+    /// many different types.
+    ///
+    /// ```
+    /// # use any_dyn::DynPtr;
+    /// # use core::{fmt::Debug, mem::ManuallyDrop, ptr::NonNull};
+    /// let mut slot = ManuallyDrop::new(String::from("pooled"));
+    /// let ptr = DynPtr::new(NonNull::from(&mut *slot as &mut dyn Debug));
+    /// // Safety: The String is owned by `slot`, which will not drop it again.
+    /// assert!(unsafe { ptr.drop_in_place::<dyn Debug>() });
+    /// ```
+    ///
+    /// # Safety
+    ///
+    /// This has all of the requirements of [`core::ptr::drop_in_place`]. In
+    /// particular, the caller must ensure all of the following:
+    ///
+    /// - The object is live, has not already been dropped, and is not
+    ///   borrowed anywhere else.
+    /// - This pointer was created from a pointer that is valid for writes,
+    ///   such as from a `&mut` reference or an owning raw pointer, rather than
+    ///   from a shared reference.
+    /// - The caller logically owns the object, and nothing else will drop it
+    ///   or use it again afterwards, except to reuse or free its memory.
+    #[inline]
+    pub unsafe fn drop_in_place<Dyn: TraitObject + ?Sized + 'static>(self) -> bool {
+        let Some(ptr) = self.cast::<Dyn>() else {
+            return false;
+        };
+        // Safety: The caller must uphold the requirements above.
+        unsafe { core::ptr::drop_in_place(ptr.as_ptr()) };
+        true
+    }
+
+    /// Returns a byte slice covering the memory of the object this pointer
+    /// refers to, with the size reported by the trait object metadata, if and
+    /// only if it was constructed from a trait object of type `Dyn`.
+    ///
+    /// This is intended only for hashing or content-addressing "plain old
+    /// data" objects, and is almost never what you want.
+    ///
+    /// # Safety
+    ///
+    /// This is extremely unsafe. The caller must ensure all of the following:
+    ///
+    /// - The object is live and not mutated for the whole of `'a`.
+    /// - Every byte of the object is initialized. In particular, the object's
+    ///   type must not have any padding bytes, and must not contain
+    ///   [`MaybeUninit`] or unions whose bytes might be uninitialized.
+    /// - The object does not use interior mutability, such as
+    ///   [`Cell`](core::cell::Cell), unless nothing mutates it during `'a`.
+    ///
+    /// Even when these requirements are met, the bytes of pointers and
+    /// references inside the object are just addresses, and do not
+    /// represent the data they refer to.
+    #[inline]
+    pub unsafe fn value_bytes<'a, Dyn: TraitObject + ?Sized + 'static>(&self) -> Option<&'a [u8]> {
+        let size = self.layout_as::<Dyn>()?.size();
+        // Safety: The caller must uphold the requirements above, and the
+        // size comes from the object's own trait object metadata.
+        Some(unsafe { core::slice::from_raw_parts(self.thin.as_ptr() as *const u8, size) })
+    }
+
+    /// Returns the size in bytes of the object this pointer refers to, if and
+    /// only if it was constructed from a trait object of type `Dyn`.
+    ///
+    /// This is read from the trait object metadata, which records the size of
+    /// the concrete type alongside its vtable, so it does not access the
+    /// object itself. The result is the same as [`core::mem::size_of_val`]
+    /// applied to the original trait object reference.
+    ///
+    /// ```
+    /// # use any_dyn::DynPtr;
+    /// # use core::{fmt::Debug, ptr::NonNull};
+    /// let ptr = DynPtr::new(NonNull::from(&[0_u16; 3] as &dyn Debug));
+    /// assert_eq!(ptr.value_size::<dyn Debug>(), Some(6));
+    /// assert_eq!(ptr.value_align::<dyn Debug>(), Some(2));
+    /// ```
+    ///
+    /// The trait object type must be given because [`DynPtr`] does not retain
+    /// any way to interpret the metadata without it.
+    #[inline]
+    pub fn value_size<Dyn: TraitObject + ?Sized + 'static>(&self) -> Option<usize> {
+        Some(self.layout_as::<Dyn>()?.size())
+    }
+
+    /// Returns the alignment in bytes of the object this pointer refers to, if
+    /// and only if it was constructed from a trait object of type `Dyn`.
+    ///
+    /// As with [`DynPtr::value_size`], this is read from the trait object
+    /// metadata. The result is the same as [`core::mem::align_of_val`]
+    /// applied to the original trait object reference.
+    #[inline]
+    pub fn value_align<Dyn: TraitObject + ?Sized + 'static>(&self) -> Option<usize> {
+        Some(self.layout_as::<Dyn>()?.align())
+    }
+
+    /// Returns the layout of the object this pointer refers to, as reported by
+    /// its trait object metadata, if and only if it was constructed from a
+    /// trait object of type `Dyn`.
+    #[inline]
+    fn layout_as<Dyn: TraitObject + ?Sized + 'static>(&self) -> Option<Layout> {
+        Some(core::ptr::metadata(self.cast::<Dyn>()?.as_ptr()).layout())
+    }
+
+    /// Conjures a [`Dyn`] with an arbitrary lifetime from this pointer.
     ///
-    /// Callers can recover `from` by calling [`Dyn::cast`] with the
-    /// same trait object type.
+    /// This is equivalent to [`Dyn::from_raw`].
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure that the resulting lifetime is correct for
+    /// the object behind this pointer, and that the object is not mutated
+    /// while the result is in use.
     #[inline]
-    pub fn new<Dyn: TraitObject + ?Sized + 'static>(from: &'a Dyn) -> Self {
-        let ptr = DynPtr::new(NonNull::from(from));
-        // Safety: We're returning with the same lifetime we were given.
-        unsafe { Self::from_raw(ptr) }
+    pub const unsafe fn as_shared_handle<'a>(self) -> Dyn<'a> {
+        // Safety: The caller must uphold the same requirements.
+        unsafe { Dyn::from_raw(self) }
     }
 
-    /// Conjures a [`Dyn`] with an arbitrary lifetime from a [`DynPtr`].
+    /// Conjures a [`DynMut`] with an arbitrary lifetime from this pointer.
+    ///
+    /// This is equivalent to [`DynMut::from_raw`].
     ///
     /// # Safety
     ///
     /// The caller must ensure that the resulting lifetime is correct for
-    /// the object behind the given pointer.
+    /// the object behind this pointer, and that nothing else accesses the
+    /// object while the result is in use. In particular, a pointer obtained
+    /// from a [`Dyn`] must not be used to create a [`DynMut`] unless the
+    /// caller can guarantee exclusive access by some other means.
     #[inline]
-    pub const unsafe fn from_raw(ptr: DynPtr) -> Self {
-        Self {
-            ptr,
-            _phantom: PhantomData,
+    pub const unsafe fn as_mut_handle<'a>(self) -> DynMut<'a> {
+        // Safety: The caller must uphold the same requirements.
+        unsafe { DynMut::from_raw(self) }
+    }
+
+    /// Returns the [`DynTypeId`] of the trait object type this pointer was
+    /// constructed from.
+    ///
+    /// [`DynPtr::cast`] succeeds only for the trait object type this
+    /// identifies.
+    #[inline]
+    pub const fn dyn_type_id(&self) -> DynTypeId {
+        DynTypeId {
+            type_id: self.type_id,
         }
     }
 
-    /// Cast returns a reference to a trait object of type `Dyn` if and only if
-    /// this [`Dyn`] value was constructed from a trait object of the same
-    /// type.
+    /// Returns `true` if `self` and `other` refer to the same object viewed
+    /// through the same trait.
+    ///
+    /// This compares the address of the object and the trait object type,
+    /// similar to [`Arc::ptr_eq`](https://doc.rust-lang.org/std/sync/struct.Arc.html#method.ptr_eq).
+    /// It does not compare the trait object metadata, because the same
+    /// vtable can potentially be duplicated at different addresses.
+    ///
+    /// As with any address comparison, distinct zero-sized objects may share
+    /// the same address and therefore compare as equal.
+    ///
+    /// ```
+    /// # use any_dyn::Dyn;
+    /// # use core::fmt::{Debug, Display};
+    /// let a = 1_u8;
+    /// let b = 1_u8;
+    /// let a_debug = Dyn::new(&a as &dyn Debug);
+    /// assert!(a_debug.ptr_eq(&Dyn::new(&a as &dyn Debug)));
+    /// assert!(!a_debug.ptr_eq(&Dyn::new(&a as &dyn Display)));
+    /// assert!(!a_debug.ptr_eq(&Dyn::new(&b as &dyn Debug)));
+    /// ```
     #[inline]
-    pub fn cast<Dyn: TraitObject + ?Sized + 'static>(self) -> Option<&'a Dyn> {
-        self.ptr.cast::<Dyn>().map(|ptr| unsafe {
-            // Safety: DynPtr guarantees that it will only return Some
-            // if the following is safe.
-            ptr.as_ref()
-        })
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        self.thin == other.thin && self.type_id == other.type_id
     }
 
-    /// Returns the underlying [`DynPtr`] for this trait object reference.
+    /// Returns `true` if `self` and `other` refer to the same object,
+    /// regardless of which traits they are viewed through.
+    ///
+    /// This compares only the address of the object. As with any address
+    /// comparison, distinct zero-sized objects may share the same address
+    /// and therefore be considered the same object.
     #[inline]
-    pub const fn as_ptr(self) -> DynPtr {
-        self.ptr
+    pub fn same_object(&self, other: &Self) -> bool {
+        self.thin == other.thin
     }
-}
 
-/// A mutable reference to a trait object for an erased trait tracked only at
-/// runtime.
-///
-/// This is essentially the same as [`Dyn`] except that it represents a
-/// mutable reference instead of a shared reference.
-///
-/// In other words, this is like `&'a mut dyn Trait`, but with `Trait` tracked
-/// dynamically instead of statically,
-#[derive(Debug, Clone, Copy)]
-pub struct DynMut<'a> {
-    ptr: DynPtr,
-    _phantom: PhantomData<&'a mut ()>,
-}
+    /// Returns the address of the object this pointer refers to, followed by
+    /// the address of its vtable, for use in logging and tracing.
+    ///
+    /// This can help to correlate log entries about the same object, or about
+    /// objects viewed through the same trait implementation. The addresses
+    /// are not useful for anything else, and in particular the same vtable
+    /// might be duplicated at more than one address.
+    ///
+    /// The vtable address depends on an implementation detail of the Rust
+    /// standard library: that [`DynMetadata`] is represented as a single
+    /// pointer to the vtable. If a future version of Rust changes that then
+    /// the second element of the result will always be zero.
+    #[inline]
+    pub fn addresses(&self) -> (usize, usize) {
+        (self.thin.addr().get(), self.metadata.vtable_addr())
+    }
 
-impl<'a> DynMut<'a> {
-    /// Creates a [`DynMut`] value that represents the same trait object
-    /// given in `from`, but with the specific trait erased as runtime data
-    /// instead of part of the result type.
+    /// Returns `true` if the memory of the objects that `self` and `other`
+    /// refer to overlap, given the sizes of each.
+    fn overlaps(&self, other: &Self, (a_size, b_size): (usize, usize)) -> bool {
+        let (a_start, b_start) = (self.thin.addr().get(), other.thin.addr().get());
+        let (a_end, b_end) = (a_start + a_size, b_start + b_size);
+        a_start < b_end && b_start < a_end
+    }
+
+    /// Associates this pointer with the current generation of `generation`,
+    /// so that later casts can detect if the generation has since advanced.
     ///
-    /// Callers can recover `from` by calling [`DynMut::cast`] with the
-    /// same trait object type.
+    /// If the generation has advanced by the time [`DynPtr::cast`] is called,
+    /// the cast panics. Refer to [`Generation`] for more information.
+    ///
+    /// The association is recorded only when debug assertions are enabled.
+    /// Otherwise this returns the pointer unchanged, and [`DynPtr`] is the
+    /// same size as it would be without the `debug-validity` feature.
+    ///
+    /// This is available only when the `debug-validity` feature is enabled.
+    #[cfg(feature = "debug-validity")]
     #[inline]
-    pub fn new<Dyn: TraitObject + ?Sized + 'static>(from: &'a mut Dyn) -> Self {
-        let ptr = DynPtr::new(NonNull::from(from));
-        // Safety: We're returning with the same lifetime we were given.
-        unsafe { Self::from_raw(ptr) }
+    pub fn with_generation(self, generation: &'static Generation) -> Self {
+        #[cfg(debug_assertions)]
+        return Self {
+            generation: Some(GenerationTag::new(generation)),
+            ..self
+        };
+        #[cfg(not(debug_assertions))]
+        {
+            let _ = generation;
+            self
+        }
     }
 
-    /// Conjures a [`DynMut`] with an arbitrary lifetime from a [`DynPtr`].
+    /// Like [`DynPtr::with_generation`], but for a generation that is only
+    /// borrowed.
     ///
     /// # Safety
     ///
-    /// The caller must ensure that the resulting lifetime is correct for
-    /// the object behind the given pointer.
+    /// The result and all copies of it must either be discarded or passed
+    /// through [`DynPtr::without_borrowed_generation`] before `generation`
+    /// is moved or dropped.
+    #[cfg(feature = "debug-validity")]
     #[inline]
-    pub const unsafe fn from_raw(ptr: DynPtr) -> Self {
-        Self {
-            ptr,
-            _phantom: PhantomData,
+    unsafe fn with_borrowed_generation(self, generation: &Generation) -> Self {
+        #[cfg(debug_assertions)]
+        return Self {
+            // Safety: The caller must uphold the requirements above.
+            generation: Some(unsafe { GenerationTag::new_borrowed(generation) }),
+            ..self
+        };
+        #[cfg(not(debug_assertions))]
+        {
+            let _ = generation;
+            self
         }
     }
 
-    /// Cast returns a reference to a trait object of type `Dyn` if and only if
-    /// this [`DynMut`] value was constructed from a trait object of the same
-    /// type.
+    /// Returns this pointer without any generation that was associated with
+    /// it by [`Dyn::with_generation`] or [`DynMut::with_generation`], so that
+    /// it can outlive the borrow of that generation.
     #[inline]
-    pub fn cast<Dyn: TraitObject + ?Sized + 'static>(self) -> Option<&'a mut Dyn> {
-        self.ptr.cast::<Dyn>().map(|mut ptr| unsafe {
-            // Safety: DynPtr guarantees that it will only return Some
-            // if the following is safe.
-            ptr.as_mut()
-        })
+    const fn without_borrowed_generation(self) -> Self {
+        #[cfg(all(feature = "debug-validity", debug_assertions))]
+        if let Some(tag) = self.generation
+            && tag.is_borrowed()
+        {
+            return Self {
+                generation: None,
+                ..self
+            };
+        }
+        self
     }
 
-    /// Returns the underlying [`DynPtr`] for this trait object reference.
+    /// Replaces the trait object metadata and trait object type of this
+    /// pointer, while keeping the same data pointer and any debugging
+    /// information, such as where the pointer was constructed.
+    ///
+    /// This is intended only for test harnesses that need to present an
+    /// object as implementing a different trait than it was erased with, such
+    /// as when injecting faults into dispatch logic. Normal code should
+    /// construct a new [`DynPtr`] from a correctly-typed trait object instead.
+    ///
+    /// # Safety
+    ///
+    /// The object this pointer refers to must actually be valid to use through
+    /// `metadata`: that is, combining the data pointer with `metadata` must
+    /// produce a valid `NonNull<Dyn>`. Any [`Dyn`] or [`DynMut`] later derived
+    /// from this pointer will allow calling methods through the new vtable
+    /// without any further checks, so violating this causes undefined
+    /// behavior.
+    ///
+    /// # Panics
+    ///
+    /// Panics under the same conditions as [`DynPtr::new`].
+    #[track_caller]
+    pub unsafe fn replace_trait<Dyn: TraitObject + ?Sized + 'static>(
+        &mut self,
+        metadata: DynMetadata<Dyn>,
+    ) {
+        assert!(
+            Self::layout_assumption_holds::<Dyn>(),
+            "DynMetadata types no longer have fixed layout regardless of type parameter",
+        );
+        // Everything else, including any debugging information, still
+        // describes the same object and so is kept as is.
+        self.metadata = ErasedMetadata::erase(metadata);
+        self.type_id = TypeId::of::<Dyn>();
+    }
+
+    /// Like [`DynPtr::cast`], but also returns the [`DynTypeId`] of the trait
+    /// object type that matched, for situations where both are needed.
     #[inline]
-    pub const fn as_ptr(self) -> DynPtr {
-        self.ptr
+    pub fn cast_with_id<Dyn: TraitObject + ?Sized + 'static>(
+        &self,
+    ) -> Option<(NonNull<Dyn>, DynTypeId)> {
+        self.cast::<Dyn>().map(|ptr| (ptr, self.dyn_type_id()))
+    }
+
+    /// Like [`DynPtr::cast`], but panics if the trait object type doesn't
+    /// match.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this [`DynPtr`] value was not constructed from a trait object
+    /// of type `Dyn`. The panic is reported at the caller's location and, if
+    /// the `debug-diagnostics` feature is enabled, the message also includes
+    /// the location where this [`DynPtr`] was constructed.
+    #[inline]
+    #[track_caller]
+    pub fn expect_cast<Dyn: TraitObject + ?Sized + 'static>(&self) -> NonNull<Dyn> {
+        match self.cast::<Dyn>() {
+            Some(ptr) => ptr,
+            None => self.cast_failed(core::any::type_name::<Dyn>()),
+        }
+    }
+
+    /// Returns the source location where this [`DynPtr`] was constructed.
+    ///
+    /// This is available only when the `debug-diagnostics` feature is enabled.
+    #[cfg(feature = "debug-diagnostics")]
+    #[inline]
+    pub const fn created_at(&self) -> &'static core::panic::Location<'static> {
+        self.created_at
+    }
+
+    #[cold]
+    #[track_caller]
+    fn cast_failed(&self, want: &str) -> ! {
+        #[cfg(feature = "minimal-panic")]
+        {
+            let _ = want;
+            panic!("DynPtr is not a trait object of type expected by caller");
+        }
+        #[cfg(all(feature = "debug-diagnostics", not(feature = "minimal-panic")))]
+        panic!(
+            "DynPtr created at {} is not a trait object of type {want}",
+            self.created_at
+        );
+        #[cfg(not(any(feature = "debug-diagnostics", feature = "minimal-panic")))]
+        panic!("DynPtr is not a trait object of type {want}");
     }
 }
 
-/// A non-null raw pointer to a trait object for an arbitrary trait decided at
-/// runtime.
-///
-/// A value of this type is similar to a `NonNull<dyn Trait>`, but with the
-/// specific trait erased so it can vary at runtime.
+/// [`DynPtr`] values compare by identity: two values are equal if they refer
+/// to the same address viewed through the same trait object type, exactly as
+/// for [`DynPtr::ptr_eq`]. This never compares the objects themselves.
 ///
-/// This is the raw pointer version of [`Dyn`] and [`DynMut`], which
-/// therefore does not track any lifetimes. Those other two types are wrappers
-/// around this which track the lifetime and mutability of the underlying
-/// object.
-#[derive(Debug, Clone, Copy)]
-pub struct DynPtr {
-    thin: NonNull<()>,
-    metadata: MaybeUninit<DynMetadata<()>>,
-    type_id: TypeId,
+/// The hash is derived from the same address and trait object type, and so
+/// is stable for as long as the object stays at the same address. That makes
+/// [`DynPtr`] and [`Dyn`] suitable as keys in hash-based maps and sets that
+/// track a collection of distinct objects.
+impl PartialEq for DynPtr {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.ptr_eq(other)
+    }
 }
 
-impl DynPtr {
-    /// Creates a [`DynPtr`] value that represents the same trait object
-    /// given in `from`, but with the specific trait erased as runtime data
-    /// instead of part of the result type.
-    ///
-    /// Callers can recover `from` by calling [`DynPtr::cast`] with the
-    /// same trait object type.
-    pub fn new<Dyn: TraitObject + ?Sized + 'static>(from: NonNull<Dyn>) -> Self {
-        // The following is to make it more likely that we'll notice quickly
-        // if the implementation detail we're relying on changes in a future
-        // version of Rust. Note that we're only depending on equal layout and
-        // not on identical representation, because we never actually try to
-        // access the target data as the erased type.
-        assert_eq!(
-            const { Layout::new::<DynMetadata<Dyn>>() },
-            const { Layout::new::<DynMetadata<()>>() },
-            "DynMetadata types no longer have fixed layout regardless of type parameter",
-        );
+impl Eq for DynPtr {}
 
-        let thin = from.cast::<()>();
-        let metadata = core::ptr::metadata(from.as_ptr());
-        let type_id = core::any::TypeId::of::<Dyn>();
+impl core::hash::Hash for DynPtr {
+    #[inline]
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        self.thin.hash(state);
+        self.type_id.hash(state);
+    }
+}
+
+/// Trait object metadata with its trait object type erased.
+///
+/// The metadata is copied verbatim into an opaque container whose layout
+/// matches `DynMetadata<()>`, but we never actually access it as that type:
+/// [`ErasedMetadata::restore`] turns it back into the original
+/// `DynMetadata<Dyn>` before it's used. This relies only on all
+/// `DynMetadata` types having the same layout, which [`DynPtr::new`] checks,
+/// and not on them having the same representation or validity invariants.
+///
+/// All of the type punning of metadata happens in this type's methods.
+#[derive(Clone, Copy)]
+struct ErasedMetadata(MaybeUninit<DynMetadata<()>>);
 
-        // We copy the metadata verbatim into an opaque container whose
-        // layout matches `DynMetadata<()>`, but we never actually access
-        // it as that type: we'll turn this back into DynMetadata<Dyn>
-        // again before we actually try to make use of it.
-        let mut erased_metadata = MaybeUninit::<DynMetadata<()>>::uninit();
-        let erased_metadata_ptr = erased_metadata.as_mut_ptr();
-        let our_metadata_erased =
-            &metadata as *const <Dyn as Pointee>::Metadata as *const DynMetadata<()>;
+impl ErasedMetadata {
+    /// Erases the trait object type of the given metadata.
+    ///
+    /// The caller must already have checked
+    /// [`DynPtr::layout_assumption_holds`] for `Dyn`.
+    #[inline]
+    const fn erase<Dyn: TraitObject + ?Sized + 'static>(metadata: DynMetadata<Dyn>) -> Self {
+        let mut erased = MaybeUninit::<DynMetadata<()>>::uninit();
+        // Safety: The two types have the same layout, and the destination
+        // is a local that we own.
         unsafe {
-            core::ptr::copy_nonoverlapping(our_metadata_erased, erased_metadata_ptr, 1);
+            core::ptr::copy_nonoverlapping(
+                &metadata as *const DynMetadata<Dyn> as *const DynMetadata<()>,
+                erased.as_mut_ptr(),
+                1,
+            );
         }
+        Self(erased)
+    }
 
-        Self {
-            thin,
-            metadata: erased_metadata,
-            type_id,
-        }
+    /// Recovers the original metadata.
+    ///
+    /// # Safety
+    ///
+    /// This must have been created by [`ErasedMetadata::erase`] for the same
+    /// trait object type `Dyn`.
+    #[inline]
+    unsafe fn restore<Dyn: TraitObject + ?Sized + 'static>(&self) -> DynMetadata<Dyn> {
+        // Safety: The caller must ensure that this holds a DynMetadata<Dyn>.
+        unsafe { core::ptr::read(self.0.as_ptr() as *const DynMetadata<Dyn>) }
     }
 
-    /// Cast returns a pointer to a trait object of type `Dyn` if and only if
-    /// this [`DynPtr`] value was constructed from a trait object of the same
-    /// type.
+    /// Returns the address of the vtable, or zero if [`DynMetadata`] is not
+    /// represented as a single pointer.
     #[inline]
-    pub fn cast<Dyn: TraitObject + ?Sized + 'static>(&self) -> Option<NonNull<Dyn>> {
-        if core::any::TypeId::of::<Dyn>() != self.type_id {
-            return None;
+    fn vtable_addr(&self) -> usize {
+        if size_of::<DynMetadata<()>>() == size_of::<*const ()>() {
+            // Safety: The metadata was copied from an initialized
+            // DynMetadata, which we've just checked is pointer-sized.
+            unsafe { self.0.as_ptr().cast::<*const ()>().read() }.addr()
+        } else {
+            0
         }
-        let metadata_ptr = self.metadata.as_ptr() as *const <Dyn as Pointee>::Metadata;
-        let metadata = unsafe {
-            // Safety: If this object was constructed correctly then our
-            // erased metadata is for the requested trait object type.
-            core::ptr::read(metadata_ptr)
-        };
-        let ptr = core::ptr::from_raw_parts_mut::<Dyn>(self.thin.as_ptr(), metadata);
-        Some(unsafe {
-            // Safety: ptr was built from a NonNull<()>, so is definitely not
-            // null itself.
-            NonNull::new_unchecked(ptr)
-        })
+    }
+}
+
+impl core::fmt::Debug for ErasedMetadata {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        self.0.fmt(f)
     }
 }
 
@@ -407,12 +1928,73 @@ pub struct DynTypeId {
 impl DynTypeId {
     /// Returns the [`DynTypeId`] of the type parameter `Dyn`, which must be
     /// a trait object type.
+    ///
+    /// Using a type that is not a trait object type is a compile-time error:
+    ///
+    /// ```compile_fail,E0277
+    /// # use any_dyn::DynTypeId;
+    /// struct NotATraitObject;
+    /// DynTypeId::of::<NotATraitObject>(); // `NotATraitObject` is not a trait object type
+    /// ```
     #[inline]
     pub const fn of<Dyn: TraitObject + ?Sized + 'static>() -> Self {
         Self {
             type_id: core::any::TypeId::of::<Dyn>(),
         }
     }
+
+    /// Returns a hash of this trait object type identity that can be exchanged
+    /// between separately-loaded libraries from the same build.
+    ///
+    /// The result is derived deterministically from the [`TypeId`] of the
+    /// trait object type, using a fixed hash function rather than one with a
+    /// random seed, so it's consistent for the same trait object type across
+    /// all crates compiled together by the same compiler with the same
+    /// dependency graph. That makes it suitable for identifying traits
+    /// between plugins that were built together, such as when passing trait
+    /// objects across a dynamic library boundary.
+    ///
+    /// It is _not_ consistent across different compiler versions, or across
+    /// builds with different dependency versions, so it must not be stored
+    /// persistently or exchanged with separately-built programs.
+    ///
+    /// [`TypeId`] does not expose its full internal value, so this is computed
+    /// from what its [`Hash`](core::hash::Hash) implementation reveals, which
+    /// is currently only 64 bits. Distinct trait object types can therefore
+    /// have the same result, although that's very unlikely. Code that relies
+    /// on this for soundness, such as before casting a pointer received from
+    /// another library, should treat a collision as a possibility.
+    ///
+    /// ```
+    /// # use any_dyn::DynTypeId;
+    /// # use core::fmt::{Debug, Display};
+    /// let debug = DynTypeId::of::<dyn Debug>();
+    /// assert_eq!(debug.stable_hash(), DynTypeId::of::<dyn Debug>().stable_hash());
+    /// assert_ne!(debug.stable_hash(), DynTypeId::of::<dyn Display>().stable_hash());
+    /// ```
+    pub fn stable_hash(self) -> u128 {
+        use core::hash::{Hash, Hasher};
+
+        /// 128-bit FNV-1a, chosen because it's simple and has no seed.
+        struct Fnv1a128(u128);
+
+        impl Hasher for Fnv1a128 {
+            fn write(&mut self, bytes: &[u8]) {
+                for &b in bytes {
+                    self.0 ^= u128::from(b);
+                    self.0 = self.0.wrapping_mul(0x0000000001000000000000000000013b);
+                }
+            }
+
+            fn finish(&self) -> u64 {
+                self.0 as u64
+            }
+        }
+
+        let mut hasher = Fnv1a128(0x6c62272e07bb014262b821756295c58d);
+        self.type_id.hash(&mut hasher);
+        hasher.0
+    }
 }
 
 /// A trait that's implemented by all trait object types, and no other types.
@@ -431,7 +2013,64 @@ impl DynTypeId {
 /// type-erased identity and values using [`DynTypeId`], [`Dyn`], [`DynMut`],
 /// etc.
 ///
-/// # Safety:
+/// For example, type-erased trait objects cannot be created for trait object
+/// types that have a non-`'static` lifetime bound:
+///
+/// ```compile_fail,E0521
+/// # use any_dyn::Dyn;
+/// # trait SomeTrait {}
+/// fn erase<'a>(obj: &'a (dyn SomeTrait + 'a)) -> Dyn<'a> {
+///     Dyn::new(obj) // `dyn SomeTrait + 'a` is not `'static`
+/// }
+/// ```
+///
+/// ...nor for types that are not trait objects at all:
+///
+/// ```compile_fail,E0271
+/// # use any_dyn::Dyn;
+/// let value = 5_i32;
+/// Dyn::new(&value); // `i32` is not a trait object type
+/// ```
+///
+/// That includes dynamically-sized types other than trait objects, such as
+/// slices, `str`, and structs whose last field is a slice. Pointers to those
+/// types also carry metadata, but it's a length rather than
+/// [`DynMetadata`], and this library requires the
+/// metadata to be exactly `DynMetadata<Self>`:
+///
+/// ```compile_fail,E0277
+/// # use any_dyn::DynPtr;
+/// # use core::ptr::NonNull;
+/// struct Packet {
+///     len: u8,
+///     data: [u8],
+/// }
+/// fn erase(packet: &Packet) -> DynPtr {
+///     DynPtr::new(NonNull::from(packet)) // `Packet` is not a trait object type
+/// }
+/// ```
+///
+/// The concrete type behind a trait object is always sized, because only
+/// sized types can be coerced into trait objects, so there is no way to
+/// erase a dynamically-sized value without first putting it behind a sized
+/// wrapper such as a `Box`.
+///
+/// Only `dyn`-compatible traits have trait object types at all, so traits
+/// with associated constants or generic associated types cannot be used
+/// with this library. Traits with ordinary associated types are
+/// `dyn`-compatible, but each choice of associated types is a distinct trait
+/// object type: `dyn Iterator<Item = u32>` and `dyn Iterator<Item = u64>`
+/// have different [`DynTypeId`] values, for example.
+///
+/// ```compile_fail,E0038
+/// # use any_dyn::DynTypeId;
+/// trait WithConst {
+///     const VALUE: u32;
+/// }
+/// DynTypeId::of::<dyn WithConst>(); // `WithConst` is not dyn-compatible
+/// ```
+///
+/// # Safety
 ///
 /// This trait has a blanket implementation across all trait object types,
 /// which is the only implementation allowed. No implementations outside of
@@ -450,6 +2089,7 @@ pub unsafe trait TraitObject:
 /// any `dyn`-compatible trait.
 unsafe impl<Dyn: ?Sized> TraitObject for Dyn
 where
+    <Dyn as Pointee>::Metadata: sealed::DynMetadataFor<Dyn>,
     Dyn: core::ptr::Pointee<Metadata = core::ptr::DynMetadata<Dyn>>,
 {
     #[inline(always)]
@@ -461,5 +2101,130 @@ where
     }
 }
 
+/// Conversion into [`Dyn`], for generic code that accepts any trait object
+/// reference.
+///
+/// This is implemented for `&'a dyn Trait` for any trait object type whose
+/// trait is `'static`, and also for [`Dyn`] itself, so that a function taking
+/// `impl IntoDyn<'a>` can accept either a typed or an already-erased trait
+/// object reference:
+///
+/// ```
+/// # use any_dyn::{Dyn, IntoDyn};
+/// # use core::fmt::{Debug, Display};
+/// fn store<'a>(slots: &mut Vec<Dyn<'a>>, obj: impl IntoDyn<'a>) {
+///     slots.push(obj.into_dyn());
+/// }
+///
+/// let (a, b) = (1_u8, "two");
+/// let mut slots = Vec::new();
+/// store(&mut slots, &a as &dyn Debug);
+/// store(&mut slots, &b as &dyn Display);
+/// store(&mut slots, Dyn::new(&a as &dyn Display));
+/// assert!(slots[1].cast::<dyn Display>().is_some());
+/// assert!(slots[2].cast::<dyn Display>().is_some());
+/// ```
+///
+/// This trait is sealed, so it cannot be implemented outside of this crate.
+pub trait IntoDyn<'a>: sealed::IntoDynSealed {
+    /// Erases the trait object type of this reference.
+    fn into_dyn(self) -> Dyn<'a>;
+}
+
+impl<'a, T: TraitObject + ?Sized + 'static> IntoDyn<'a> for &'a T {
+    #[inline]
+    #[track_caller]
+    fn into_dyn(self) -> Dyn<'a> {
+        Dyn::new(self)
+    }
+}
+
+impl<'a> IntoDyn<'a> for Dyn<'a> {
+    #[inline]
+    fn into_dyn(self) -> Dyn<'a> {
+        self
+    }
+}
+
+/// Conversion into [`DynMut`], for generic code that accepts any mutable
+/// trait object reference.
+///
+/// This is the mutable counterpart of [`IntoDyn`], implemented for
+/// `&'a mut dyn Trait` and for [`DynMut`] itself.
+///
+/// ```
+/// # use any_dyn::{DynMut, IntoDynMut};
+/// # use core::fmt::Write;
+/// fn write_hello<'a>(out: impl IntoDynMut<'a>) -> bool {
+///     match out.into_dyn_mut().cast::<dyn Write>() {
+///         Some(w) => w.write_str("hello").is_ok(),
+///         None => false,
+///     }
+/// }
+///
+/// let mut s = String::new();
+/// assert!(write_hello(&mut s as &mut dyn Write));
+/// assert_eq!(s, "hello");
+/// ```
+///
+/// This trait is sealed, so it cannot be implemented outside of this crate.
+pub trait IntoDynMut<'a>: sealed::IntoDynMutSealed {
+    /// Erases the trait object type of this reference.
+    fn into_dyn_mut(self) -> DynMut<'a>;
+}
+
+impl<'a, T: TraitObject + ?Sized + 'static> IntoDynMut<'a> for &'a mut T {
+    #[inline]
+    #[track_caller]
+    fn into_dyn_mut(self) -> DynMut<'a> {
+        DynMut::new(self)
+    }
+}
+
+impl<'a> IntoDynMut<'a> for DynMut<'a> {
+    #[inline]
+    fn into_dyn_mut(self) -> DynMut<'a> {
+        self
+    }
+}
+
+mod sealed {
+    use core::ptr::DynMetadata;
+
+    /// Implemented only for [`DynMetadata`], so that the blanket
+    /// implementation of [`TraitObject`](crate::TraitObject) can report a
+    /// clearer error when it's used with a type that is not a trait object.
+    ///
+    /// This is redundant with the `Pointee<Metadata = DynMetadata<Self>>`
+    /// bound, but the compiler only uses custom diagnostic messages for
+    /// unimplemented traits, and not for mismatched associated types.
+    #[diagnostic::on_unimplemented(
+        message = "`{T}` is not a trait object type",
+        label = "expected a `dyn Trait` type",
+        note = "type-erased trait objects and `DynTypeId` require a trait object type like `dyn Trait`, not a sized type or another kind of dynamically-sized type such as a slice"
+    )]
+    pub trait DynMetadataFor<T: ?Sized> {}
+
+    impl<T: ?Sized> DynMetadataFor<T> for DynMetadata<T> {}
+
+    /// Restricts [`IntoDyn`](crate::IntoDyn) to the implementations in this
+    /// crate.
+    pub trait IntoDynSealed {}
+
+    impl<T: crate::TraitObject + ?Sized + 'static> IntoDynSealed for &T {}
+    impl IntoDynSealed for crate::Dyn<'_> {}
+
+    /// Restricts [`IntoDynMut`](crate::IntoDynMut) to the implementations in
+    /// this crate.
+    pub trait IntoDynMutSealed {}
+
+    impl<T: crate::TraitObject + ?Sized + 'static> IntoDynMutSealed for &mut T {}
+    impl IntoDynMutSealed for crate::DynMut<'_> {}
+
+    /// Restricts [`DynViews`](crate::DynViews) to the tuple implementations
+    /// in this crate.
+    pub trait DynViewsSealed {}
+}
+
 #[cfg(test)]
 mod tests;
@@ -0,0 +1,93 @@
+use alloc::sync::Arc;
+use core::{
+    ptr::NonNull,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use crate::{DynPtr, DynTypeId, TraitObject};
+
+/// A [`DynPtr`] paired with a shared validity flag, so that every copy of the
+/// handle can be invalidated at once.
+///
+/// Cloning a [`RevocableDyn`] produces another handle that shares the same
+/// flag. Once any of them calls [`RevocableDyn::revoke`],
+/// [`RevocableDyn::cast`] returns `None` for all of them. A plugin host can
+/// use this to keep one clone for itself and hand others out, revoking them
+/// all when the plugin that owns the object is unloaded.
+///
+/// ```
+/// # use any_dyn::{DynPtr, RevocableDyn};
+/// # use core::{fmt::Display, ptr::NonNull};
+/// let value = 5;
+/// let host = RevocableDyn::new(DynPtr::new(NonNull::from(&value as &dyn Display)));
+/// let given_out = host.clone();
+/// assert!(given_out.cast::<dyn Display>().is_some());
+///
+/// host.revoke();
+/// assert!(given_out.is_revoked());
+/// assert!(given_out.cast::<dyn Display>().is_none());
+/// ```
+///
+/// Revocation only prevents _future_ casts. It cannot invalidate a pointer
+/// that [`RevocableDyn::cast`] already returned, and so the host must
+/// separately ensure that no such pointer is still in use before it actually
+/// destroys the object, such as by waiting for in-flight calls into the
+/// plugin to complete.
+///
+/// This is available only when the `alloc` feature is enabled and the
+/// target supports atomic pointer-sized operations.
+#[derive(Debug, Clone)]
+pub struct RevocableDyn {
+    ptr: DynPtr,
+    valid: Arc<AtomicBool>,
+}
+
+impl RevocableDyn {
+    /// Creates a new, not-yet-revoked handle for the given pointer.
+    #[inline]
+    pub fn new(ptr: DynPtr) -> Self {
+        Self {
+            ptr,
+            valid: Arc::new(AtomicBool::new(true)),
+        }
+    }
+
+    /// Revokes this handle and all of its clones, so that any subsequent
+    /// call to [`RevocableDyn::cast`] on any of them returns `None`.
+    ///
+    /// Revoking an already-revoked handle has no effect.
+    #[inline]
+    pub fn revoke(&self) {
+        self.valid.store(false, Ordering::Release);
+    }
+
+    /// Returns true if [`RevocableDyn::revoke`] has been called on this
+    /// handle or any of its clones.
+    #[inline]
+    pub fn is_revoked(&self) -> bool {
+        !self.valid.load(Ordering::Acquire)
+    }
+
+    /// Returns a pointer to a trait object of type `Dyn` if and only if this
+    /// handle has not been revoked and it was constructed from a trait object
+    /// of the same type.
+    ///
+    /// As with [`DynPtr::cast`], the result is a raw pointer and it's the
+    /// caller's responsibility to use it only while the object remains live.
+    #[inline]
+    pub fn cast<Dyn: TraitObject + ?Sized + 'static>(&self) -> Option<NonNull<Dyn>> {
+        if self.is_revoked() {
+            return None;
+        }
+        self.ptr.cast()
+    }
+
+    /// Returns the [`DynTypeId`] of the trait object type this handle was
+    /// constructed from.
+    ///
+    /// This remains available after revocation.
+    #[inline]
+    pub const fn dyn_type_id(&self) -> DynTypeId {
+        self.ptr.dyn_type_id()
+    }
+}
@@ -0,0 +1,226 @@
+use core::ptr::NonNull;
+
+use crate::{Dyn, DynPtr, DynTypeId, TraitObject};
+
+/// A `'static` shared reference to a thread-safe trait object for an erased
+/// trait tracked only at runtime.
+///
+/// This is like a [`Dyn<'static>`](Dyn), except that it implements [`Send`]
+/// and [`Sync`] and can therefore be used in `static` items, such as global
+/// registries of trait objects. To make that sound, [`StaticDyn::new`] accepts
+/// only trait object types that are themselves [`Sync`], such as
+/// `dyn Trait + Sync` or `dyn Trait + Send + Sync`.
+///
+/// [`StaticDyn::new`] is a `const fn`, so no lazy initialization is required:
+///
+/// ```
+/// # use any_dyn::{DynTypeId, StaticDyn};
+/// trait WithMessage {
+///     fn message(&self) -> &'static str;
+/// }
+///
+/// struct HelloWorld;
+///
+/// impl WithMessage for HelloWorld {
+///     fn message(&self) -> &'static str {
+///         "Hello, world!"
+///     }
+/// }
+///
+/// static REGISTRY: &[(DynTypeId, StaticDyn)] = &[(
+///     DynTypeId::of::<dyn WithMessage + Sync>(),
+///     StaticDyn::new(&HelloWorld as &(dyn WithMessage + Sync)),
+/// )];
+///
+/// let (_, obj) = REGISTRY
+///     .iter()
+///     .find(|(type_id, _)| *type_id == DynTypeId::of::<dyn WithMessage + Sync>())
+///     .unwrap();
+/// assert_eq!(obj.cast::<dyn WithMessage + Sync>().unwrap().message(), "Hello, world!");
+/// ```
+///
+/// Note that `dyn WithMessage + Sync` is a different trait object type than
+/// `dyn WithMessage`, so casts must use the same trait object type, including
+/// auto traits, that the [`StaticDyn`] was constructed with.
+#[derive(Debug, Clone, Copy)]
+pub struct StaticDyn {
+    ptr: DynPtr,
+}
+
+// Safety: StaticDyn can only be constructed from a `&'static Dyn` where
+// `Dyn: Sync`, and such references are both Send and Sync. The only access
+// StaticDyn offers to the object is through shared references, so no other
+// thread-safety requirement applies. The trait object metadata is immutable
+// data with no thread affinity of its own.
+unsafe impl Send for StaticDyn {}
+unsafe impl Sync for StaticDyn {}
+
+impl StaticDyn {
+    /// Creates a [`StaticDyn`] value that represents the same trait object
+    /// given in `from`, but with the specific trait erased as runtime data
+    /// instead of part of the result type.
+    #[inline]
+    #[track_caller]
+    pub const fn new<Dyn: TraitObject + Sync + ?Sized + 'static>(from: &'static Dyn) -> Self {
+        Self {
+            ptr: DynPtr::new(NonNull::from_ref(from)),
+        }
+    }
+
+    /// Conjures a [`StaticDyn`] from a [`DynPtr`].
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure that the object behind the given pointer lives
+    /// for the rest of the program and is never mutated, and that the trait
+    /// object type the pointer was constructed from is [`Sync`].
+    #[inline]
+    pub const unsafe fn from_raw(ptr: DynPtr) -> Self {
+        Self { ptr }
+    }
+
+    /// Creates a [`StaticDyn`] value from an [`Arc`](alloc::sync::Arc) by
+    /// leaking one of its strong references, so that the object will never be
+    /// dropped.
+    ///
+    /// This is intended for objects created at runtime that must then remain
+    /// available for the rest of the program, such as entries in a global
+    /// registry populated during startup.
+    ///
+    /// ```
+    /// # use any_dyn::StaticDyn;
+    /// # use core::fmt::Display;
+    /// # use std::sync::{Arc, OnceLock};
+    /// static GREETING: OnceLock<StaticDyn> = OnceLock::new();
+    ///
+    /// let shared: Arc<dyn Display + Sync> = Arc::new(String::from("hello"));
+    /// GREETING.set(StaticDyn::leak_arc(Arc::clone(&shared))).unwrap();
+    /// let thread = std::thread::spawn(|| {
+    ///     let greeting = GREETING.get().unwrap();
+    ///     greeting.cast::<dyn Display + Sync>().unwrap().to_string()
+    /// });
+    /// assert_eq!(thread.join().unwrap(), "hello");
+    /// ```
+    ///
+    /// This is available only when the `alloc` feature is enabled.
+    #[cfg(feature = "alloc")]
+    #[inline]
+    #[track_caller]
+    pub fn leak_arc<Dyn: TraitObject + Sync + ?Sized + 'static>(
+        from: alloc::sync::Arc<Dyn>,
+    ) -> Self {
+        let ptr = alloc::sync::Arc::into_raw(from);
+        // Safety: We never release the strong reference we took from the
+        // Arc, so the object lives for the rest of the program.
+        Self::new(unsafe { &*ptr })
+    }
+
+    /// Returns a reference to a trait object of type `Dyn` if and only if
+    /// this [`StaticDyn`] value was constructed from a trait object of the
+    /// same type.
+    #[inline]
+    pub fn cast<Dyn: TraitObject + ?Sized + 'static>(self) -> Option<&'static Dyn> {
+        self.as_dyn().cast::<Dyn>()
+    }
+
+    /// Returns the equivalent [`Dyn`] for this trait object reference.
+    #[inline]
+    pub const fn as_dyn(self) -> Dyn<'static> {
+        // Safety: StaticDyn can only be constructed from a 'static reference.
+        unsafe { Dyn::from_raw(self.ptr) }
+    }
+
+    /// Returns the underlying [`DynPtr`] for this trait object reference.
+    #[inline]
+    pub const fn as_ptr(self) -> DynPtr {
+        self.ptr
+    }
+
+    /// Returns the [`DynTypeId`] of the trait object type this value was
+    /// constructed from.
+    #[inline]
+    pub const fn dyn_type_id(self) -> DynTypeId {
+        self.ptr.dyn_type_id()
+    }
+}
+
+/// A lazily-initialized [`StaticDyn`], for exposing global singletons as
+/// trait objects.
+///
+/// The initialization function runs the first time the value is requested,
+/// and its result is then returned by all subsequent requests. This allows
+/// the object to be created at runtime, such as by leaking a
+/// [`Box`](alloc::boxed::Box) or an [`Arc`](std::sync::Arc), while still
+/// declaring it in a `static` item:
+///
+/// ```
+/// # use any_dyn::{LazyStaticDyn, StaticDyn};
+/// # use std::sync::Arc;
+/// trait Greeter {
+///     fn greet(&self) -> String;
+/// }
+///
+/// struct Config {
+///     name: String,
+/// }
+///
+/// impl Greeter for Config {
+///     fn greet(&self) -> String {
+///         format!("hello, {}", self.name)
+///     }
+/// }
+///
+/// static GREETER: LazyStaticDyn = LazyStaticDyn::new(|| {
+///     let config = Config { name: std::env::var("USER").unwrap_or_default() };
+///     StaticDyn::leak_arc(Arc::new(config) as Arc<dyn Greeter + Send + Sync>)
+/// });
+///
+/// let greeter = GREETER.cast::<dyn Greeter + Send + Sync>().unwrap();
+/// assert!(greeter.greet().starts_with("hello"));
+/// ```
+///
+/// As with [`StaticDyn`] itself, the trait object type must be [`Sync`],
+/// because any thread can access the object once it's initialized. If
+/// several threads request the value at once then only one of them runs the
+/// initialization function, and the others wait for it to complete.
+///
+/// This is available only when the `std` feature is enabled.
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub struct LazyStaticDyn {
+    cell: std::sync::OnceLock<StaticDyn>,
+    init: fn() -> StaticDyn,
+}
+
+#[cfg(feature = "std")]
+impl LazyStaticDyn {
+    /// Creates a [`LazyStaticDyn`] that will be initialized by calling `init`
+    /// when first used.
+    #[inline]
+    pub const fn new(init: fn() -> StaticDyn) -> Self {
+        Self {
+            cell: std::sync::OnceLock::new(),
+            init,
+        }
+    }
+
+    /// Returns the [`StaticDyn`], initializing it first if necessary.
+    #[inline]
+    pub fn get(&self) -> StaticDyn {
+        *self.cell.get_or_init(self.init)
+    }
+
+    /// Returns a reference to a trait object of type `Dyn` if and only if the
+    /// initialization function produced a trait object of the same type,
+    /// initializing it first if necessary.
+    #[inline]
+    pub fn cast<Dyn: TraitObject + ?Sized + 'static>(&self) -> Option<&'static Dyn> {
+        self.get().cast::<Dyn>()
+    }
+
+    /// Returns the equivalent [`Dyn`], initializing it first if necessary.
+    #[inline]
+    pub fn as_dyn(&self) -> Dyn<'static> {
+        self.get().as_dyn()
+    }
+}
@@ -58,6 +58,45 @@ fn arced() {
 }
 
 #[test]
+#[cfg(feature = "alloc")]
+fn smart_pointers_without_deref() {
+    extern crate alloc;
+    use alloc::boxed::Box;
+    use alloc::rc::Rc;
+    use alloc::sync::Arc;
+
+    let boxed: Box<dyn AsTraitObject> = Box::new(Implementer);
+    let rced: Rc<dyn AsTraitObject> = Rc::new(Implementer);
+    let arced: Arc<dyn AsTraitObject> = Arc::new(Implementer);
+    let not_boxed: Box<dyn AsTraitObject> = Box::new(NotImplementer);
+
+    for obj in [&boxed as &dyn AsTraitObject, &rced, &arced, &&boxed] {
+        let with_message_ref =
+            cast_trait_object::<dyn WithMessage>(obj).expect("can't cast to WithMessage");
+        assert_eq!(with_message_ref.message(), "hello from implementer");
+    }
+    assert!(cast_trait_object::<dyn WithMessage>(&not_boxed).is_none());
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+fn send_sync_handles() {
+    extern crate alloc;
+    use alloc::boxed::Box;
+
+    let boxed: Box<dyn AsTraitObject + Send + Sync> = Box::new(Implementer);
+    let from_deref: &dyn WithMessage =
+        cast_trait_object::<dyn WithMessage>(&*boxed).expect("can't cast to WithMessage");
+    let from_box: &dyn WithMessage =
+        cast_trait_object::<dyn WithMessage>(&boxed).expect("can't cast to WithMessage");
+    assert_eq!(from_deref.message(), "hello from implementer");
+    assert_eq!(from_box.message(), "hello from implementer");
+}
+
+#[test]
+// This test is written out step-by-step to illustrate the intended usage,
+// rather than in the most compact form.
+#[allow(clippy::vec_init_then_push, clippy::manual_map)]
 fn dyn_handles() {
     extern crate alloc;
     use alloc::boxed::Box;
@@ -112,3 +151,1892 @@ fn dyn_handles() {
         ]
     );
 }
+
+#[test]
+#[cfg(feature = "alloc")]
+fn dyn_handles_capability_matrix() {
+    use crate::traitcast::capability_matrix;
+    use core::any::Any;
+
+    // This mirrors the table in `dyn_handles`, but asks every handle about
+    // every trait at once, as someone debugging that table might.
+    let objs: [&dyn AsTraitObject; 4] =
+        [&Implementer, &NotImplementer, &Implementer, &NotImplementer];
+    let traits = [
+        DynTypeId::of::<dyn WithMessage>(),
+        DynTypeId::of::<dyn AsTraitObject>(),
+        DynTypeId::of::<dyn Any>(),
+    ];
+
+    let matrix = capability_matrix(&objs, &traits);
+    assert_eq!(
+        matrix,
+        [
+            [true, false, false],
+            [false, false, false],
+            [true, false, false],
+            [false, false, false],
+        ]
+    );
+    assert!(
+        capability_matrix(&objs, &[])
+            .iter()
+            .all(|row| row.is_empty())
+    );
+    assert!(capability_matrix(&[], &traits).is_empty());
+}
+
+#[test]
+#[should_panic(expected = "is not a trait object of type")]
+fn expect_cast_mismatch() {
+    let erased = Dyn::new(&Implementer as &dyn WithMessage);
+    erased.expect_cast::<dyn AsTraitObject>();
+}
+
+#[test]
+#[cfg(feature = "minimal-panic")]
+#[should_panic(expected = "DynPtr is not a trait object of type expected by caller")]
+fn expect_cast_mismatch_minimal() {
+    let erased = Dyn::new(&Implementer as &dyn WithMessage);
+    erased.expect_cast::<dyn AsTraitObject>();
+}
+
+#[test]
+fn higher_ranked_trait_objects() {
+    use crate::DynMut;
+
+    trait Borrowing {
+        fn longer<'x>(&'x self, other: &'x str) -> &'x str;
+    }
+
+    trait Parser<'a> {
+        fn parse(&mut self, input: &'a str) -> &'a str;
+    }
+
+    struct Sentence(&'static str);
+
+    impl Borrowing for Sentence {
+        fn longer<'x>(&'x self, other: &'x str) -> &'x str {
+            if other.len() > self.0.len() {
+                other
+            } else {
+                self.0
+            }
+        }
+    }
+
+    struct Trimmer;
+
+    impl<'a> Parser<'a> for Trimmer {
+        fn parse(&mut self, input: &'a str) -> &'a str {
+            input.trim()
+        }
+    }
+
+    let sentence = Sentence("hello world");
+    let erased = Dyn::new(&sentence as &dyn Borrowing);
+    let borrowing = erased
+        .cast::<dyn Borrowing>()
+        .expect("can't cast to Borrowing");
+    assert_eq!(borrowing.longer("hi"), "hello world");
+
+    // A higher-ranked trait object type is distinct from any of its
+    // specific instantiations.
+    assert_ne!(
+        DynTypeId::of::<dyn for<'a> Parser<'a>>(),
+        DynTypeId::of::<dyn Parser<'static>>()
+    );
+
+    let mut trimmer = Trimmer;
+    let erased = DynMut::new(&mut trimmer as &mut dyn for<'a> Parser<'a>);
+    assert!(erased.cast::<dyn Parser<'static>>().is_none());
+    let parser = erased
+        .cast::<dyn for<'a> Parser<'a>>()
+        .expect("can't cast to Parser");
+    let buf = *b"  trimmed  ";
+    let input = core::str::from_utf8(&buf).unwrap();
+    assert_eq!(parser.parse(input), "trimmed");
+}
+
+#[test]
+fn replace_trait() {
+    use crate::DynPtr;
+    use core::ptr::NonNull;
+
+    let implementer = Implementer;
+    let mut ptr = DynPtr::new(NonNull::from(&implementer as &dyn WithMessage));
+    #[cfg(feature = "debug-diagnostics")]
+    let created_at = ptr.created_at();
+    let metadata = core::ptr::metadata(&implementer as &dyn AsTraitObject);
+    // Safety: The data pointer refers to an Implementer, which does implement
+    // AsTraitObject.
+    unsafe { ptr.replace_trait(metadata) };
+
+    #[cfg(feature = "debug-diagnostics")]
+    assert_eq!(ptr.created_at(), created_at);
+    assert!(ptr.cast::<dyn WithMessage>().is_none());
+    let as_trait_object = ptr
+        .cast::<dyn AsTraitObject>()
+        .expect("can't cast to AsTraitObject");
+    // Safety: implementer is still live.
+    let as_trait_object = unsafe { as_trait_object.as_ref() };
+    assert!(cast_trait_object::<dyn WithMessage>(as_trait_object).is_some());
+}
+
+#[cfg(all(feature = "debug-validity", debug_assertions))]
+#[test]
+#[should_panic(expected = "generation")]
+fn replace_trait_keeps_generation() {
+    use crate::{DynPtr, Generation};
+    use core::ptr::NonNull;
+
+    static GENERATION: Generation = Generation::new();
+
+    let implementer = Implementer;
+    let mut ptr =
+        DynPtr::new(NonNull::from(&implementer as &dyn WithMessage)).with_generation(&GENERATION);
+    let metadata = core::ptr::metadata(&implementer as &dyn AsTraitObject);
+    // Safety: The data pointer refers to an Implementer, which does implement
+    // AsTraitObject.
+    unsafe { ptr.replace_trait(metadata) };
+
+    GENERATION.advance();
+    ptr.cast::<dyn AsTraitObject>();
+}
+
+#[test]
+fn downcast_concrete_send_sync() {
+    use crate::DynMut;
+    use core::any::Any;
+
+    let mut value = 5_u32;
+    let erased = DynMut::new(&mut value as &mut (dyn Any + Send + Sync));
+    assert!(erased.downcast_concrete::<u32>().is_none());
+    assert!(erased.downcast_concrete_send_sync::<u64>().is_none());
+    *erased
+        .downcast_concrete_send_sync::<u32>()
+        .expect("can't downcast to u32") += 1;
+    assert_eq!(value, 6);
+
+    let erased = Dyn::new(&value as &(dyn Any + Send + Sync));
+    assert_eq!(erased.downcast_concrete_send_sync::<u32>(), Some(&6));
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+#[should_panic(expected = "TraitTable has no entry for")]
+fn trait_table_index_missing() {
+    use crate::traitcast::TraitTable;
+
+    let mut table = TraitTable::new();
+    table.insert(Dyn::new(&Implementer as &dyn WithMessage));
+    let _ = table[DynTypeId::of::<dyn AsTraitObject>()];
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "misaligned")]
+fn misaligned_ptr() {
+    use crate::DynPtr;
+    use core::fmt::Debug;
+    use core::ptr::NonNull;
+
+    let values = [0_u32; 2];
+    let metadata = core::ptr::metadata(&values[0] as &dyn Debug);
+    // Safety: The offset pointer is still within the same allocation.
+    let misaligned = unsafe { NonNull::from(&values).cast::<u8>().add(1) };
+    let ptr = DynPtr::new(NonNull::<dyn Debug>::from_raw_parts(misaligned, metadata));
+    ptr.cast::<dyn Debug>();
+}
+
+#[test]
+fn manually_drop() {
+    use crate::DynMut;
+    use core::mem::ManuallyDrop;
+
+    trait Counter {
+        fn increment(&mut self);
+    }
+
+    struct ExampleCounter(usize);
+
+    impl Counter for ExampleCounter {
+        fn increment(&mut self) {
+            self.0 += 1;
+        }
+    }
+
+    let implementer = ManuallyDrop::new(Implementer);
+    let erased = Dyn::new(&*implementer as &dyn WithMessage);
+    let with_message = erased
+        .cast::<dyn WithMessage>()
+        .expect("can't cast to WithMessage");
+    assert_eq!(with_message.message(), "hello from implementer");
+
+    let mut counter = ManuallyDrop::new(ExampleCounter(0));
+    let erased = DynMut::new(&mut *counter as &mut dyn Counter);
+    erased
+        .cast::<dyn Counter>()
+        .expect("can't cast to Counter")
+        .increment();
+    assert_eq!(ManuallyDrop::into_inner(counter).0, 1);
+}
+
+#[test]
+fn forward_through_newtype() {
+    use crate::traitcast::forward_as_trait_object;
+
+    #[repr(transparent)]
+    struct Wrapper(Implementer);
+    forward_as_trait_object!(Wrapper => self.0 : WithMessage);
+
+    struct Named {
+        inner: Implementer,
+    }
+    forward_as_trait_object!(Named => self.inner : WithMessage);
+
+    let wrapper = Wrapper(Implementer);
+    let obj = cast_trait_object::<dyn WithMessage>(&wrapper).unwrap();
+    assert_eq!(obj.message(), "hello from implementer");
+    assert!(core::ptr::addr_eq(obj, &wrapper.0));
+
+    let named = Named { inner: Implementer };
+    let obj = cast_trait_object::<dyn WithMessage>(&named).unwrap();
+    assert_eq!(obj.message(), "hello from implementer");
+}
+
+#[test]
+fn associated_types() {
+    use crate::DynMut;
+
+    let mut numbers = [1_u32, 2, 3].into_iter();
+    let erased = DynMut::new(&mut numbers as &mut dyn Iterator<Item = u32>);
+    assert_ne!(
+        DynTypeId::of::<dyn Iterator<Item = u32>>(),
+        DynTypeId::of::<dyn Iterator<Item = u64>>()
+    );
+    assert!(erased.cast::<dyn Iterator<Item = u64>>().is_none());
+    let iter = erased
+        .cast::<dyn Iterator<Item = u32>>()
+        .expect("can't cast to Iterator<Item = u32>");
+    assert_eq!(iter.sum::<u32>(), 6);
+}
+
+#[test]
+fn value_bytes() {
+    use crate::DynPtr;
+    use core::ptr::NonNull;
+
+    let pod = 0x01020304_u32;
+    let ptr = DynPtr::new(NonNull::from(&pod as &dyn core::fmt::Debug));
+    // Safety: u32 has no padding and pod remains live and unmodified.
+    let bytes = unsafe { ptr.value_bytes::<dyn core::fmt::Debug>() };
+    assert_eq!(bytes, Some(&pod.to_ne_bytes()[..]));
+    // Safety: As above, although this fails the trait object type check.
+    assert_eq!(unsafe { ptr.value_bytes::<dyn core::fmt::Display>() }, None);
+}
+
+#[test]
+fn cast_disjoint_mut_overlapping() {
+    use crate::{DynMut, DynPtr};
+    use core::{fmt::Debug, ptr::NonNull};
+
+    struct Outer {
+        inner: u32,
+    }
+
+    impl Debug for Outer {
+        fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+            self.inner.fmt(f)
+        }
+    }
+
+    let mut outer = Outer { inner: 1 };
+    let outer_raw: *mut Outer = &mut outer;
+    // Safety: These two handles alias, but cast_disjoint_mut must detect
+    // that and so never produce references from them.
+    let (outer_ptr, inner_ptr) = unsafe {
+        (
+            DynMut::from_raw(DynPtr::new(NonNull::new_unchecked(
+                outer_raw as *mut dyn Debug,
+            ))),
+            DynMut::from_raw(DynPtr::new(NonNull::new_unchecked(
+                &raw mut (*outer_raw).inner as *mut dyn Debug,
+            ))),
+        )
+    };
+    assert!(DynMut::cast_disjoint_mut::<dyn Debug>(outer_ptr, inner_ptr).is_none());
+    assert!(DynMut::cast_disjoint_mut::<dyn Debug>(outer_ptr, outer_ptr).is_none());
+
+    // A zero-sized object has no bytes to overlap, but two handles to it
+    // are still handles to the same object.
+    #[derive(Debug)]
+    struct Empty;
+    let mut empty = Empty;
+    let empty_ptr = DynMut::new(&mut empty as &mut dyn Debug);
+    assert!(DynMut::cast_disjoint_mut::<dyn Debug>(empty_ptr, empty_ptr).is_none());
+}
+
+#[test]
+fn errors() {
+    #[derive(Debug)]
+    struct ExampleError;
+
+    impl core::fmt::Display for ExampleError {
+        fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+            f.write_str("example error")
+        }
+    }
+
+    impl core::error::Error for ExampleError {}
+
+    let err = ExampleError;
+    let erased = Dyn::new_error(&err);
+    assert!(erased.cast_error().is_some());
+    assert!(erased.cast_error_send_sync().is_none());
+
+    let erased = Dyn::new_error_send_sync(&err);
+    assert!(erased.cast_error().unwrap().is::<ExampleError>());
+    assert!(erased.cast_error_send_sync().is_some());
+}
+
+#[test]
+fn fixed_trait_table() {
+    use crate::traitcast::FixedTraitTable;
+    use core::fmt::{Debug, Display};
+
+    let value = 5_u32;
+    let other = 6_u32;
+    let mut table = FixedTraitTable::<2>::new();
+    assert!(table.is_empty());
+    assert!(
+        table
+            .insert(Dyn::new(&value as &dyn Debug))
+            .unwrap()
+            .is_none()
+    );
+    assert!(
+        table
+            .insert(Dyn::new(&value as &dyn Display))
+            .unwrap()
+            .is_none()
+    );
+    assert_eq!(table.len(), 2);
+
+    // Replacing an existing entry succeeds even though the table is full.
+    let replaced = table.insert(Dyn::new(&other as &dyn Debug)).unwrap();
+    assert!(replaced.unwrap().cast::<dyn Debug>().is_some());
+
+    // Adding a new entry fails because the table is full.
+    let rejected = table
+        .insert(Dyn::new(&Implementer as &dyn WithMessage))
+        .unwrap_err();
+    assert!(rejected.cast::<dyn WithMessage>().is_some());
+
+    let debug = table.get(DynTypeId::of::<dyn Debug>()).unwrap();
+    assert_eq!(
+        debug
+            .cast::<dyn Debug>()
+            .map(|d| d as *const dyn Debug as *const u32),
+        Some(&other as *const u32)
+    );
+    assert!(cast_trait_object::<dyn Display>(&table).is_some());
+    assert!(table.get(DynTypeId::of::<dyn WithMessage>()).is_none());
+
+    assert!(table.remove(DynTypeId::of::<dyn Debug>()).is_some());
+    assert_eq!(table.len(), 1);
+    assert!(
+        table
+            .insert(Dyn::new(&Implementer as &dyn WithMessage))
+            .is_ok()
+    );
+}
+
+#[test]
+fn tagged_roles() {
+    use crate::DynTagged;
+
+    const PRIMARY: u64 = 1;
+    const FALLBACK: u64 = 2;
+
+    let primary = 1_u32;
+    let fallback = 2_u32;
+    let table = [
+        DynTagged::new(&primary as &dyn core::fmt::Debug, PRIMARY),
+        DynTagged::new(&fallback as &dyn core::fmt::Debug, FALLBACK),
+    ];
+
+    let found = table
+        .iter()
+        .find_map(|entry| entry.cast_tagged::<dyn core::fmt::Debug>(FALLBACK))
+        .unwrap();
+    assert!(core::ptr::addr_eq(found, &fallback));
+    assert!(
+        table
+            .iter()
+            .all(|entry| entry.cast_tagged::<dyn core::fmt::Debug>(3).is_none())
+    );
+    assert!(table[0].cast_tagged::<dyn WithMessage>(PRIMARY).is_none());
+    assert_eq!(table[1].tag(), FALLBACK);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn identity_map_keys() {
+    use core::fmt::{Debug, Display};
+    use std::collections::HashMap;
+
+    let a = 1_u8;
+    let b = 1_u8;
+    let mut seen: HashMap<Dyn<'_>, usize> = HashMap::new();
+    for (i, handle) in [
+        Dyn::new(&a as &dyn Debug),
+        Dyn::new(&b as &dyn Debug),
+        Dyn::new(&a as &dyn Display),
+        Dyn::new(&a as &dyn Debug),
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        seen.entry(handle).or_insert(i);
+    }
+
+    // a and b are equal values but distinct objects, and a is registered
+    // separately for each trait, so only the last handle is a duplicate.
+    assert_eq!(seen.len(), 3);
+    assert_eq!(seen[&Dyn::new(&a as &dyn Debug)], 0);
+    assert_eq!(seen[&Dyn::new(&b as &dyn Debug)], 1);
+    assert_eq!(seen[&Dyn::new(&a as &dyn Display)], 2);
+}
+
+#[test]
+fn cast_exclusive() {
+    use crate::DynMut;
+
+    let mut value = 0_u32;
+    let mut erased = DynMut::new(&mut value as &mut dyn core::any::Any);
+    for _ in 0..3 {
+        *erased
+            .cast_exclusive::<dyn core::any::Any>()
+            .unwrap()
+            .downcast_mut::<u32>()
+            .unwrap() += 1;
+    }
+    assert!(erased.cast_exclusive::<dyn WithMessage>().is_none());
+    assert_eq!(value, 3);
+}
+
+#[test]
+fn trait_objects() {
+    use crate::traitcast::{TraitObjects, trait_objects};
+
+    #[derive(Debug)]
+    struct Offering;
+
+    impl WithMessage for Offering {
+        fn message(&self) -> &'static str {
+            "offering"
+        }
+    }
+
+    impl AsTraitObject for Offering {
+        fn as_trait_object<'a>(&'a self, type_id: DynTypeId) -> Option<Dyn<'a>> {
+            match_dyn_type_id!(self, type_id => WithMessage)
+        }
+
+        fn trait_objects<'a>(&'a self) -> TraitObjects<'a> {
+            // The Debug entry is skipped, because as_trait_object doesn't
+            // offer it.
+            trait_objects!(self => WithMessage, core::fmt::Debug)
+        }
+    }
+
+    let mut iter = (&Offering as &dyn AsTraitObject).trait_objects();
+    let (type_id, obj) = iter.next().unwrap();
+    assert_eq!(type_id, DynTypeId::of::<dyn WithMessage>());
+    assert_eq!(obj.cast::<dyn WithMessage>().unwrap().message(), "offering");
+    assert!(iter.next().is_none());
+
+    assert_eq!(Implementer.trait_objects().count(), 0);
+}
+
+#[test]
+fn pointer_round_trip() {
+    use crate::DynPtr;
+    use core::fmt::Write;
+    use core::ptr::NonNull;
+
+    struct Buffer([u8; 4], usize);
+
+    impl Write for Buffer {
+        fn write_str(&mut self, s: &str) -> core::fmt::Result {
+            for b in s.bytes() {
+                self.0[self.1] = b;
+                self.1 += 1;
+            }
+            Ok(())
+        }
+    }
+
+    let mut buf = Buffer([0; 4], 0);
+    let original = NonNull::from(&mut buf as &mut dyn Write);
+    let erased = DynPtr::new(original);
+    let recovered = erased.cast::<dyn Write>().unwrap();
+    assert_eq!(recovered.addr(), original.addr());
+    assert!(core::ptr::eq(recovered.as_ptr(), original.as_ptr()));
+
+    // Writing through the recovered pointer is valid only if it has the
+    // same provenance as the original.
+    unsafe { (*recovered.as_ptr()).write_str("ok").unwrap() };
+    assert_eq!(&buf.0[..buf.1], b"ok");
+}
+
+#[test]
+fn arena_dyn_bump() {
+    use crate::{ArenaDyn, DynArena};
+    use core::{alloc::Layout, cell::Cell, fmt::Debug, mem::MaybeUninit, ptr::NonNull};
+
+    struct Bump {
+        buf: [Cell<MaybeUninit<u64>>; 8],
+        used: Cell<usize>,
+    }
+
+    // Safety: Each allocation is a distinct part of `buf`, and the offset
+    // only ever increases.
+    unsafe impl DynArena for Bump {
+        fn alloc_layout(&self, layout: Layout) -> NonNull<u8> {
+            assert!(layout.align() <= align_of::<u64>());
+            let slots = layout.size().div_ceil(size_of::<u64>());
+            let start = self.used.get();
+            assert!(start + slots <= self.buf.len(), "arena is full");
+            self.used.set(start + slots);
+            NonNull::from(&self.buf[start]).cast()
+        }
+    }
+
+    let arena = Bump {
+        buf: [const { Cell::new(MaybeUninit::uninit()) }; 8],
+        used: Cell::new(0),
+    };
+    let objs = [
+        ArenaDyn::alloc(&arena, Implementer, |v| v as &dyn WithMessage),
+        ArenaDyn::alloc(&arena, 5_u64, |v| v as &dyn Debug),
+    ];
+    assert_eq!(arena.used.get(), 1);
+    assert_eq!(
+        objs[0].cast::<dyn WithMessage>().unwrap().message(),
+        "hello from implementer"
+    );
+    assert!(objs[1].cast::<dyn WithMessage>().is_none());
+    assert_eq!(objs[1].dyn_type_id(), DynTypeId::of::<dyn Debug>());
+
+    // The lifetime-free pointer can be tied back to the same arena.
+    // Safety: The object belongs to `arena`, which is still borrowed.
+    let obj = unsafe { Dyn::from_raw_in(objs[1].as_ptr(), &arena) };
+    assert!(obj.cast::<dyn Debug>().is_some());
+}
+
+#[test]
+fn auto_trait_combinations() {
+    trait Base {
+        fn base(&self) -> u32;
+    }
+
+    trait Derived: Base {
+        fn derived(&self) -> u32;
+    }
+
+    struct Both;
+
+    impl Base for Both {
+        fn base(&self) -> u32 {
+            1
+        }
+    }
+
+    impl Derived for Both {
+        fn derived(&self) -> u32 {
+            2
+        }
+    }
+
+    // Each entry must succeed only when cast back to exactly the same
+    // combination of traits, and the recovered metadata must still allow
+    // calling methods of both the trait and its supertrait.
+    macro_rules! check {
+        ($($ty:ty),+) => {{
+            let erased = [$(Dyn::new(&Both as &$ty)),+];
+            let ids = [$(DynTypeId::of::<$ty>()),+];
+            for (i, obj) in erased.iter().enumerate() {
+                for (j, id) in ids.iter().enumerate() {
+                    assert_eq!(obj.dyn_type_id() == *id, i == j);
+                }
+                let mut matched = 0;
+                $(
+                    if let Some(obj) = obj.cast::<$ty>() {
+                        assert_eq!((obj.base(), obj.derived()), (1, 2));
+                        matched += 1;
+                    }
+                )+
+                assert_eq!(matched, 1);
+            }
+        }};
+    }
+
+    check!(
+        dyn Derived,
+        dyn Derived + Send,
+        dyn Derived + Sync,
+        dyn Derived + Send + Sync
+    );
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn dyn_box_clone() {
+    use crate::DynBox;
+    use alloc::boxed::Box;
+    use alloc::rc::Rc;
+    use alloc::vec::Vec;
+    use core::cell::Cell;
+
+    trait Counter {
+        fn bump(&mut self) -> u32;
+    }
+
+    struct Tracked {
+        count: u32,
+        // Counts how many Tracked values are currently alive.
+        alive: Rc<Cell<u32>>,
+    }
+
+    impl Tracked {
+        fn new(alive: &Rc<Cell<u32>>) -> Self {
+            alive.set(alive.get() + 1);
+            Self {
+                count: 0,
+                alive: Rc::clone(alive),
+            }
+        }
+    }
+
+    impl Clone for Tracked {
+        fn clone(&self) -> Self {
+            self.alive.set(self.alive.get() + 1);
+            Self {
+                count: self.count,
+                alive: Rc::clone(&self.alive),
+            }
+        }
+    }
+
+    impl Drop for Tracked {
+        fn drop(&mut self) {
+            self.alive.set(self.alive.get() - 1);
+        }
+    }
+
+    impl Counter for Tracked {
+        fn bump(&mut self) -> u32 {
+            self.count += 1;
+            self.count
+        }
+    }
+
+    let alive = Rc::new(Cell::new(0));
+    let mut original = DynBox::new_cloneable::<_, dyn Counter>(Tracked::new(&alive));
+    original.cast_mut::<dyn Counter>().unwrap().bump();
+    let not_cloneable = DynBox::new(Box::new(Tracked::new(&alive)) as Box<dyn Counter>);
+    assert!(not_cloneable.try_clone().is_none());
+
+    let mut copies: Vec<DynBox> = (0..3).map(|_| original.try_clone().unwrap()).collect();
+    assert_eq!(alive.get(), 5);
+
+    // Each copy is independent of the original and of each other.
+    assert_eq!(copies[0].cast_mut::<dyn Counter>().unwrap().bump(), 2);
+    assert_eq!(copies[0].cast_mut::<dyn Counter>().unwrap().bump(), 3);
+    assert_eq!(copies[1].cast_mut::<dyn Counter>().unwrap().bump(), 2);
+    assert_eq!(original.cast_mut::<dyn Counter>().unwrap().bump(), 2);
+
+    // Clones of clones keep the clone function.
+    copies.push(copies[0].try_clone().unwrap());
+    assert_eq!(copies[3].cast_mut::<dyn Counter>().unwrap().bump(), 4);
+
+    drop(copies);
+    drop(not_cloneable);
+    assert_eq!(alive.get(), 1);
+    drop(original);
+    assert_eq!(alive.get(), 0);
+}
+
+#[test]
+fn erase_field() {
+    use crate::traitcast::erase_field;
+
+    // The last field is unsized when Child is a trait object type.
+    struct Parent<Child: ?Sized> {
+        named: (u8, Implementer),
+        child: Child,
+    }
+
+    impl AsTraitObject for Parent<dyn WithMessage> {
+        fn as_trait_object<'a>(&'a self, type_id: DynTypeId) -> Option<Dyn<'a>> {
+            if type_id == DynTypeId::of::<dyn WithMessage>() {
+                Some(erase_field!(self.child : WithMessage))
+            } else if type_id == DynTypeId::of::<dyn core::fmt::Debug>() {
+                Some(erase_field!(self.named.0 : core::fmt::Debug))
+            } else {
+                None
+            }
+        }
+    }
+
+    let parent: &Parent<dyn WithMessage> = &Parent {
+        named: (7, Implementer),
+        child: Implementer,
+    };
+    let child = cast_trait_object::<dyn WithMessage>(&parent).unwrap();
+    assert!(core::ptr::addr_eq(child, &parent.child));
+    assert_eq!(child.message(), "hello from implementer");
+    assert!(core::ptr::addr_eq(
+        cast_trait_object::<dyn core::fmt::Debug>(&parent).unwrap(),
+        &parent.named.0,
+    ));
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn cast_all_mut_vec() {
+    use crate::DynMut;
+    use crate::traitcast::{AsTraitObjectMut, cast_all_mut_vec, match_dyn_type_id_mut};
+    use alloc::boxed::Box;
+
+    trait Counter {
+        fn bump(&mut self);
+        fn count(&self) -> u32;
+    }
+
+    struct Counting(u32);
+    struct NotCounting;
+
+    impl Counter for Counting {
+        fn bump(&mut self) {
+            self.0 += 1;
+        }
+
+        fn count(&self) -> u32 {
+            self.0
+        }
+    }
+
+    impl AsTraitObjectMut for Counting {
+        fn as_trait_object_mut<'a>(&'a mut self, type_id: DynTypeId) -> Option<DynMut<'a>> {
+            match_dyn_type_id_mut!(self, type_id => Counter)
+        }
+    }
+
+    impl AsTraitObjectMut for NotCounting {}
+
+    let mut objs: [Box<dyn AsTraitObjectMut>; 3] = [
+        Box::new(Counting(0)),
+        Box::new(NotCounting),
+        Box::new(Counting(10)),
+    ];
+    let mut counters = cast_all_mut_vec::<dyn Counter>(&mut objs);
+    assert_eq!(counters.len(), 2);
+    for counter in &mut counters {
+        counter.bump();
+    }
+    counters[1].bump();
+    assert_eq!(counters[0].count(), 1);
+    assert_eq!(counters[1].count(), 12);
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn dyn_box_downcast() {
+    use crate::DynBox;
+    use alloc::boxed::Box;
+    use alloc::string::String;
+
+    trait Consume {
+        fn consume(self: Box<Self>) -> String;
+    }
+
+    struct Message(String);
+
+    impl Consume for Message {
+        fn consume(self: Box<Self>) -> String {
+            self.0
+        }
+    }
+
+    let boxed = DynBox::new(Box::new(Message("owned".into())) as Box<dyn Consume>);
+    let Err(boxed) = boxed.downcast::<dyn WithMessage>() else {
+        panic!("cast to the wrong trait object type succeeded");
+    };
+    let consumable = boxed.downcast::<dyn Consume>().unwrap();
+    assert_eq!(consumable.consume(), "owned");
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn erase_slice() {
+    let a = Implementer;
+    let b = Implementer;
+    let objs: [&dyn WithMessage; 2] = [&a, &b];
+    let erased = Dyn::erase_slice(&objs);
+    assert_eq!(erased.len(), 2);
+    for (obj, orig) in erased.iter().zip(objs) {
+        assert!(core::ptr::addr_eq(
+            obj.cast::<dyn WithMessage>().unwrap(),
+            orig
+        ));
+    }
+}
+
+#[test]
+fn swap_if_same_trait() {
+    use crate::DynMut;
+    use core::fmt::{Debug, Display};
+
+    let mut a = 1_u32;
+    let mut b = 2_u32;
+    let mut c = 3_u64;
+    let mut a_debug = DynMut::new(&mut a as &mut dyn Debug);
+    let mut b_display = DynMut::new(&mut b as &mut dyn Display);
+    let mut c_debug = DynMut::new(&mut c as &mut dyn Debug);
+    // Safety: These calls fail the trait and layout checks, so nothing is
+    // actually swapped.
+    unsafe {
+        assert!(!a_debug.swap_if_same_trait::<dyn Debug>(&mut b_display));
+        assert!(!a_debug.swap_if_same_trait::<dyn Debug>(&mut c_debug));
+    }
+    assert_eq!((a, b, c), (1, 2, 3));
+
+    let mut a_debug = DynMut::new(&mut a as &mut dyn Debug);
+    let mut b_debug = DynMut::new(&mut b as &mut dyn Debug);
+    // Safety: Both objects are u32.
+    unsafe {
+        assert!(a_debug.swap_if_same_trait::<dyn Debug>(&mut b_debug));
+    }
+    assert_eq!((a, b), (2, 1));
+}
+
+#[test]
+fn generic_methods_excluded_from_vtable() {
+    trait Visitor {
+        fn visit(&self) -> u32;
+
+        // Generic methods make a trait dyn-incompatible unless they are
+        // excluded from the vtable like this.
+        fn visit_with<F: Fn(u32) -> u32>(&self, f: F) -> u32
+        where
+            Self: Sized,
+        {
+            f(self.visit())
+        }
+
+        fn into_value(self) -> u32
+        where
+            Self: Sized,
+        {
+            self.visit()
+        }
+    }
+
+    struct Seven;
+
+    impl Visitor for Seven {
+        fn visit(&self) -> u32 {
+            7
+        }
+    }
+
+    assert_eq!(Seven.visit_with(|n| n * 2), 14);
+    assert_eq!(Seven.into_value(), 7);
+    let erased = Dyn::new(&Seven as &dyn Visitor);
+    assert_eq!(erased.cast::<dyn Visitor>().unwrap().visit(), 7);
+}
+
+#[test]
+fn handle_chain() {
+    use crate::traitcast::HandleChain;
+
+    #[derive(Debug)]
+    struct Second;
+
+    impl WithMessage for Second {
+        fn message(&self) -> &'static str {
+            "hello from second"
+        }
+    }
+
+    impl AsTraitObject for Second {
+        fn as_trait_object<'a>(&'a self, type_id: DynTypeId) -> Option<Dyn<'a>> {
+            // WithMessage is also offered by an earlier source in the chain
+            // below, so this one is never used.
+            match_dyn_type_id!(self, type_id => WithMessage, core::fmt::Debug)
+        }
+    }
+
+    let first = Implementer;
+    let second = Second;
+    let sources: [&dyn AsTraitObject; 3] = [&NotImplementer, &first, &second];
+    let chain = HandleChain::new(&sources);
+    assert_eq!(
+        cast_trait_object::<dyn WithMessage>(&chain)
+            .unwrap()
+            .message(),
+        "hello from implementer"
+    );
+    assert!(core::ptr::addr_eq(
+        cast_trait_object::<dyn core::fmt::Debug>(&chain).unwrap(),
+        &second
+    ));
+    assert!(cast_trait_object::<dyn core::fmt::Display>(&chain).is_none());
+}
+
+#[test]
+fn drop_in_place() {
+    use crate::DynPtr;
+    use core::mem::MaybeUninit;
+    use core::ptr::NonNull;
+    use core::sync::atomic::{AtomicU32, Ordering};
+
+    static DROPS: AtomicU32 = AtomicU32::new(0);
+
+    struct Tracked;
+
+    impl Drop for Tracked {
+        fn drop(&mut self) {
+            DROPS.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    trait Pooled {}
+    impl Pooled for Tracked {}
+
+    let mut slots = [const { MaybeUninit::<Tracked>::uninit() }; 2];
+    let ptrs = slots.each_mut().map(|slot| {
+        let obj: &mut dyn Pooled = slot.write(Tracked);
+        DynPtr::new(NonNull::from(obj))
+    });
+    assert_eq!(DROPS.load(Ordering::Relaxed), 0);
+    // Safety: The trait object type doesn't match, so nothing is dropped.
+    assert!(!unsafe { ptrs[0].drop_in_place::<dyn core::fmt::Debug>() });
+    assert_eq!(DROPS.load(Ordering::Relaxed), 0);
+    for ptr in ptrs {
+        // Safety: Each slot was initialized above and is never used again.
+        assert!(unsafe { ptr.drop_in_place::<dyn Pooled>() });
+    }
+    assert_eq!(DROPS.load(Ordering::Relaxed), 2);
+}
+
+#[test]
+fn value_size_and_align() {
+    use crate::Dyn;
+    use core::fmt::Debug;
+    use core::mem::{align_of, size_of};
+
+    #[derive(Debug)]
+    #[repr(align(16))]
+    struct Aligned(#[allow(dead_code)] u8);
+
+    let values = (1_u8, 2_u32, [3_u64; 3], Aligned(4), ());
+    let erased = [
+        Dyn::new(&values.0 as &dyn Debug),
+        Dyn::new(&values.1 as &dyn Debug),
+        Dyn::new(&values.2 as &dyn Debug),
+        Dyn::new(&values.3 as &dyn Debug),
+        Dyn::new(&values.4 as &dyn Debug),
+    ];
+    let expected = [
+        (size_of::<u8>(), align_of::<u8>()),
+        (size_of::<u32>(), align_of::<u32>()),
+        (size_of::<[u64; 3]>(), align_of::<[u64; 3]>()),
+        (size_of::<Aligned>(), align_of::<Aligned>()),
+        (0, 1),
+    ];
+    for (obj, (size, align)) in erased.iter().zip(expected) {
+        let ptr = obj.as_ptr();
+        assert_eq!(
+            (
+                ptr.value_size::<dyn Debug>(),
+                ptr.value_align::<dyn Debug>()
+            ),
+            (Some(size), Some(align))
+        );
+    }
+    assert_eq!(erased[3].as_ptr().value_size::<dyn Debug>(), Some(16));
+    assert_eq!(
+        erased[3].as_ptr().value_size::<dyn core::fmt::Display>(),
+        None
+    );
+}
+
+#[test]
+fn sorted_dispatch_table() {
+    use crate::DynTypeId;
+    use crate::traitcast::{binary_search_dispatch, sort_dispatch_table};
+
+    trait A {}
+    trait B {}
+    trait C {}
+    trait D {}
+    trait Missing {}
+
+    let ids = [
+        DynTypeId::of::<dyn A>(),
+        DynTypeId::of::<dyn B>(),
+        DynTypeId::of::<dyn C>(),
+        DynTypeId::of::<dyn D>(),
+    ];
+    let mut table = [(ids[3], 3), (ids[1], 1), (ids[0], 0), (ids[2], 2)];
+    sort_dispatch_table(&mut table);
+    assert!(table.is_sorted_by_key(|&(id, _)| id));
+
+    for (expected, id) in ids.into_iter().enumerate() {
+        assert_eq!(binary_search_dispatch(&table, id), Some(&expected));
+    }
+    assert_eq!(
+        binary_search_dispatch(&table, DynTypeId::of::<dyn Missing>()),
+        None
+    );
+    assert_eq!(binary_search_dispatch::<u8>(&[], ids[0]), None);
+}
+
+#[test]
+fn upcast_trait_objects() {
+    use crate::{Dyn, DynMut, DynTypeId};
+
+    trait Super {
+        fn name(&self) -> &'static str;
+    }
+    trait Sub: Super {
+        fn bump(&mut self);
+        fn count(&self) -> u32;
+    }
+
+    struct Counter(u32);
+
+    impl Super for Counter {
+        fn name(&self) -> &'static str {
+            "counter"
+        }
+    }
+    impl Sub for Counter {
+        fn bump(&mut self) {
+            self.0 += 1;
+        }
+        fn count(&self) -> u32 {
+            self.0
+        }
+    }
+
+    let mut obj = Counter(1);
+    let sub: &dyn Sub = &obj;
+    let sup: &dyn Super = sub;
+
+    let erased_sub = Dyn::new(sub);
+    let erased_sup = Dyn::new(sup);
+    assert_eq!(erased_sub.dyn_type_id(), DynTypeId::of::<dyn Sub>());
+    assert_eq!(erased_sup.dyn_type_id(), DynTypeId::of::<dyn Super>());
+    assert!(erased_sub.as_ptr().same_object(&erased_sup.as_ptr()));
+    assert!(!erased_sub.ptr_eq(&erased_sup));
+
+    // Each erased view casts back only to the trait it was erased as, even
+    // though `dyn Sub` could itself be upcast to `dyn Super`.
+    assert_eq!(erased_sub.cast::<dyn Sub>().unwrap().count(), 1);
+    assert!(erased_sub.cast::<dyn Super>().is_none());
+    assert_eq!(erased_sup.cast::<dyn Super>().unwrap().name(), "counter");
+    assert!(erased_sup.cast::<dyn Sub>().is_none());
+
+    // The upcast view's metadata describes the same concrete object.
+    let sup_ptr = erased_sup.as_ptr();
+    assert_eq!(
+        sup_ptr.value_size::<dyn Super>(),
+        Some(size_of::<Counter>())
+    );
+
+    // Upcasting after a cast works as usual.
+    let upcast: &dyn Super = erased_sub.cast::<dyn Sub>().unwrap();
+    assert_eq!(upcast.name(), "counter");
+
+    let sub_mut: &mut dyn Sub = &mut obj;
+    let mut erased_sub_mut = DynMut::new(sub_mut);
+    erased_sub_mut.cast_exclusive::<dyn Sub>().unwrap().bump();
+    let sup_mut: &mut dyn Super = erased_sub_mut.cast::<dyn Sub>().unwrap();
+    let erased_sup_mut = DynMut::new(sup_mut);
+    assert!(erased_sup_mut.cast::<dyn Sub>().is_none());
+    assert_eq!(obj.count(), 2);
+}
+
+#[test]
+fn cast_trait_object_recursive_cycle() {
+    use crate::traitcast::{AsTraitObject, cast_trait_object_recursive};
+    use crate::{Dyn, DynTypeId};
+    use core::cell::Cell;
+
+    trait Missing {}
+
+    // A proxy that offers itself, which would loop forever without a limit.
+    struct SelfProxy {
+        asked: Cell<usize>,
+    }
+
+    impl AsTraitObject for SelfProxy {
+        fn as_trait_object<'a>(&'a self, type_id: DynTypeId) -> Option<Dyn<'a>> {
+            self.asked.set(self.asked.get() + 1);
+            match_dyn_type_id!(self, type_id => AsTraitObject)
+        }
+    }
+
+    let obj = SelfProxy {
+        asked: Cell::new(0),
+    };
+    assert!(cast_trait_object_recursive::<dyn Missing>(&obj, 3).is_none());
+    // Each of the three levels asks for both traits, and then the final
+    // level asks only for the requested trait.
+    assert_eq!(obj.asked.get(), 7);
+}
+
+#[test]
+fn handle_equality() {
+    use crate::{Dyn, DynMut};
+    use core::fmt::{Debug, Display};
+
+    let (a, b) = (1_u8, 1_u8);
+    assert_eq!(Dyn::new(&a as &dyn Debug), Dyn::new(&a as &dyn Debug));
+    assert_ne!(Dyn::new(&a as &dyn Debug), Dyn::new(&a as &dyn Display));
+    // Equal values are still distinct objects.
+    assert_ne!(Dyn::new(&a as &dyn Debug), Dyn::new(&b as &dyn Debug));
+
+    let mut c = 2_u8;
+    let erased = DynMut::new(&mut c as &mut dyn Debug);
+    let copy = erased;
+    assert_eq!(erased, copy);
+    let mut d = 2_u8;
+    assert_ne!(erased, DynMut::new(&mut d as &mut dyn Debug));
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn dyn_pin_box_task_store() {
+    use crate::DynPinBox;
+    use alloc::boxed::Box;
+    use core::future::Future;
+    use core::pin::Pin;
+    use core::task::{Context, Poll, Waker};
+
+    // A future that isn't ready until it has been polled once before.
+    struct YieldOnce(bool);
+
+    impl Future for YieldOnce {
+        type Output = ();
+
+        fn poll(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<()> {
+            if core::mem::replace(&mut self.0, true) {
+                Poll::Ready(())
+            } else {
+                Poll::Pending
+            }
+        }
+    }
+
+    // An async block that borrows from itself across an await point, and so
+    // relies on staying pinned.
+    let fut = async {
+        let values = [1_u32, 2, 3];
+        let total = &values;
+        YieldOnce(false).await;
+        total.iter().sum::<u32>()
+    };
+    let fut: Pin<Box<dyn Future<Output = u32>>> = Box::pin(fut);
+    let mut task = DynPinBox::new(fut);
+    assert!(task.cast::<dyn Future<Output = ()>>().is_none());
+
+    let mut cx = Context::from_waker(Waker::noop());
+    let fut = task.cast_mut::<dyn Future<Output = u32>>().unwrap();
+    assert_eq!(fut.poll(&mut cx), Poll::Pending);
+
+    let Err(task) = task.downcast::<dyn Future<Output = ()>>() else {
+        unreachable!("task was erased as a different future type");
+    };
+    let mut fut = task.downcast::<dyn Future<Output = u32>>().unwrap();
+    assert_eq!(fut.as_mut().poll(&mut cx), Poll::Ready(6));
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn atomic_dyn_ptr_handoff() {
+    extern crate std;
+    use crate::{AtomicDynPtr, BoxedDynPtr, DynPtr};
+    use core::fmt::Debug;
+    use core::ptr::NonNull;
+    use core::sync::atomic::Ordering;
+    use std::vec::Vec;
+
+    static VALUES: [u32; 4] = [10, 20, 30, 40];
+    let slot = AtomicDynPtr::empty();
+
+    // Each thread publishes its own pointer and takes whichever pointer was
+    // there before, so every pointer ends up owned by exactly one place.
+    let taken: Vec<Option<BoxedDynPtr>> = std::thread::scope(|scope| {
+        let handles: Vec<_> = VALUES
+            .iter()
+            .map(|value| {
+                let slot = &slot;
+                scope.spawn(move || {
+                    let ptr = DynPtr::new(NonNull::from(value as &(dyn Debug + Sync)));
+                    slot.swap(Some(BoxedDynPtr::new(ptr)), Ordering::AcqRel)
+                })
+            })
+            .collect();
+        handles.into_iter().map(|h| h.join().unwrap()).collect()
+    });
+
+    let mut seen: Vec<u32> = taken
+        .into_iter()
+        .flatten()
+        .chain(slot.into_inner())
+        .map(|boxed| {
+            let ptr = boxed.get().cast::<dyn Debug + Sync>().unwrap();
+            // Safety: The pointers all refer to elements of a static array.
+            *unsafe { ptr.cast::<u32>().as_ref() }
+        })
+        .collect();
+    seen.sort_unstable();
+    assert_eq!(seen, VALUES);
+}
+
+#[test]
+fn addresses_for_logging() {
+    use crate::Dyn;
+    use core::fmt::{Debug, Display};
+
+    let value = 5_u32;
+    let other = 6_u32;
+    let (debug_data, debug_vtable) = Dyn::new(&value as &dyn Debug).as_ptr().addresses();
+    let (display_data, display_vtable) = Dyn::new(&value as &dyn Display).as_ptr().addresses();
+    let (other_data, _) = Dyn::new(&other as &dyn Debug).as_ptr().addresses();
+
+    assert_eq!(debug_data, &value as *const u32 as usize);
+    assert_eq!(debug_data, display_data);
+    assert_ne!(debug_data, other_data);
+    assert_ne!(debug_vtable, 0);
+    assert_ne!(debug_vtable, display_vtable);
+}
+
+#[test]
+fn match_dyn_type_id_arms() {
+    use crate::DynMut;
+    use crate::traitcast::{AsTraitObjectMut, match_dyn_type_id_mut};
+    use core::cell::Cell;
+
+    trait Counter {
+        fn bump(&mut self) -> u32;
+    }
+
+    impl Counter for u32 {
+        fn bump(&mut self) -> u32 {
+            *self += 1;
+            *self
+        }
+    }
+
+    struct Guarded {
+        count: u32,
+        exposed: Cell<u32>,
+    }
+
+    impl AsTraitObject for Guarded {
+        fn as_trait_object<'a>(&'a self, type_id: DynTypeId) -> Option<Dyn<'a>> {
+            match_dyn_type_id!(type_id => {
+                WithMessage => {
+                    self.exposed.set(self.exposed.get() + 1);
+                    &Implementer
+                },
+            })
+        }
+    }
+
+    impl AsTraitObjectMut for Guarded {
+        fn as_trait_object_mut<'a>(&'a mut self, type_id: DynTypeId) -> Option<DynMut<'a>> {
+            match_dyn_type_id_mut!(type_id => {
+                Counter => {
+                    *self.exposed.get_mut() += 1;
+                    &mut self.count
+                },
+            })
+        }
+    }
+
+    let mut obj = Guarded {
+        count: 0,
+        exposed: Cell::new(0),
+    };
+    assert!(cast_trait_object::<dyn Counter>(&obj).is_none());
+    assert_eq!(obj.exposed.get(), 0);
+    let msg = cast_trait_object::<dyn WithMessage>(&obj).unwrap();
+    assert_eq!(msg.message(), "hello from implementer");
+    assert_eq!(obj.exposed.get(), 1);
+
+    let counter = obj
+        .as_trait_object_mut(DynTypeId::of::<dyn Counter>())
+        .unwrap()
+        .cast::<dyn Counter>()
+        .unwrap();
+    assert_eq!(counter.bump(), 1);
+    assert_eq!(obj.exposed.get(), 2);
+}
+
+#[test]
+fn cast_first_of() {
+    use core::fmt::{Debug, Display};
+
+    trait Unrelated {}
+
+    let value = 3_u8;
+    let ptr = Dyn::new(&value as &dyn Display).as_ptr();
+    let ids = [
+        DynTypeId::of::<dyn Debug>(),
+        DynTypeId::of::<dyn Unrelated>(),
+        DynTypeId::of::<dyn Display>(),
+        DynTypeId::of::<dyn Display + Sync>(),
+    ];
+    assert_eq!(ptr.cast_first_of(ids), Some(2));
+    assert_eq!(ptr.cast_first_of([ids[0], ids[1]]), None);
+    assert_eq!(ptr.cast_first_of([ids[2], ids[2]]), Some(0));
+    assert_eq!(ptr.cast_first_of([]), None);
+}
+
+#[test]
+fn recast_auto_traits() {
+    use core::fmt::Debug;
+
+    let value = 7_u8;
+    let erased = Dyn::new(&value as &(dyn Debug + Send + Sync));
+
+    // Dropping auto traits is an ordinary coercion.
+    let send_only = erased
+        .recast::<dyn Debug + Send + Sync, dyn Debug + Send>(|obj| obj)
+        .unwrap();
+    let plain = send_only
+        .recast::<dyn Debug + Send, dyn Debug>(|obj| obj)
+        .unwrap();
+    assert!(send_only.cast::<dyn Debug + Send>().is_some());
+    assert!(send_only.cast::<dyn Debug + Send + Sync>().is_none());
+    assert!(plain.cast::<dyn Debug>().is_some());
+    assert!(plain.same_object(&erased));
+
+    // The original trait object type must match for the recast to happen.
+    assert!(
+        plain
+            .recast::<dyn Debug + Send, dyn Debug>(|obj| obj)
+            .is_none()
+    );
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn handle_arena() {
+    extern crate alloc;
+    use crate::{DynBox, HandleArena};
+    use alloc::boxed::Box;
+    use alloc::string::ToString;
+    use core::fmt::Display;
+
+    trait Counter {
+        fn bump(&mut self) -> u32;
+    }
+
+    impl Counter for u32 {
+        fn bump(&mut self) -> u32 {
+            *self += 1;
+            *self
+        }
+    }
+
+    let mut arena = HandleArena::new();
+    assert!(arena.is_empty());
+    let counter = arena.insert(DynBox::new(Box::new(0_u32) as Box<dyn Counter>));
+    let label = arena.insert(DynBox::new(Box::new("label") as Box<dyn Display>));
+    assert_eq!(arena.len(), 2);
+
+    // Objects can be found only as the trait they were inserted as.
+    assert_eq!(arena.cast_mut::<dyn Counter>(counter).unwrap().bump(), 1);
+    assert!(arena.cast::<dyn Display>(counter).is_none());
+    assert_eq!(
+        arena.cast::<dyn Display>(label).unwrap().to_string(),
+        "label"
+    );
+
+    // Removing an object bumps its slot's generation, so the old identifier
+    // doesn't match the object that reuses the slot.
+    let removed = arena.remove(counter).unwrap();
+    assert!(removed.cast::<dyn Counter>().is_some());
+    assert!(arena.remove(counter).is_none());
+    assert!(!arena.contains(counter));
+    assert_eq!(arena.len(), 1);
+
+    let reused = arena.insert(DynBox::new(Box::new(10_u32) as Box<dyn Counter>));
+    assert_eq!(reused.index(), counter.index());
+    assert_eq!(reused.generation(), counter.generation() + 1);
+    assert!(arena.get(counter).is_none());
+    assert!(arena.get_mut(counter).is_none());
+    assert_eq!(arena.cast_mut::<dyn Counter>(reused).unwrap().bump(), 11);
+    assert!(arena.contains(label));
+    assert_eq!(arena.len(), 2);
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn traits_implemented_for_wrappers() {
+    extern crate alloc;
+    use alloc::boxed::Box;
+
+    trait Describe {
+        fn describe(&self) -> &'static str;
+    }
+
+    struct Foo;
+
+    impl Describe for Foo {
+        fn describe(&self) -> &'static str {
+            "foo"
+        }
+    }
+    impl Describe for &'static Foo {
+        fn describe(&self) -> &'static str {
+            "reference to foo"
+        }
+    }
+    impl Describe for Box<Foo> {
+        fn describe(&self) -> &'static str {
+            "boxed foo"
+        }
+    }
+
+    static FOO: Foo = Foo;
+    let by_ref: &'static Foo = &FOO;
+    let boxed = Box::new(Foo);
+
+    let direct = Dyn::new(&FOO as &dyn Describe);
+    let through_ref = Dyn::new(&by_ref as &dyn Describe);
+    let through_box = Dyn::new(&boxed as &dyn Describe);
+
+    // All three have the same trait object type, so each casts successfully
+    // and uses its own implementation of the trait.
+    for erased in [direct, through_ref, through_box] {
+        assert_eq!(erased.dyn_type_id(), DynTypeId::of::<dyn Describe>());
+    }
+    assert_eq!(direct.cast::<dyn Describe>().unwrap().describe(), "foo");
+    assert_eq!(
+        through_ref.cast::<dyn Describe>().unwrap().describe(),
+        "reference to foo"
+    );
+    assert_eq!(
+        through_box.cast::<dyn Describe>().unwrap().describe(),
+        "boxed foo"
+    );
+
+    // The wrappers are distinct objects from the value they wrap, with the
+    // size of a pointer rather than the size of Foo.
+    assert!(!through_ref.same_object(&direct));
+    assert_eq!(
+        through_ref.as_ptr().value_size::<dyn Describe>(),
+        Some(size_of::<&Foo>())
+    );
+    assert_eq!(
+        through_box.as_ptr().value_size::<dyn Describe>(),
+        Some(size_of::<Box<Foo>>())
+    );
+    assert_eq!(direct.as_ptr().value_size::<dyn Describe>(), Some(0));
+    assert_ne!(
+        through_ref.as_ptr().addresses().1,
+        through_box.as_ptr().addresses().1
+    );
+}
+
+// This test exercises each of the places where erased trait object metadata
+// is turned back into typed metadata, and is most useful when run under Miri
+// with `cargo +nightly miri test`, which checks that each of those round
+// trips is free of undefined behavior.
+#[test]
+fn metadata_round_trip() {
+    use crate::DynPtr;
+    use core::mem::ManuallyDrop;
+    use core::ptr::NonNull;
+    use core::sync::atomic::{AtomicBool, Ordering};
+
+    trait Sum {
+        fn sum(&self) -> u64;
+    }
+
+    impl Sum for [u64; 4] {
+        fn sum(&self) -> u64 {
+            self.iter().sum()
+        }
+    }
+    impl Sum for &str {
+        fn sum(&self) -> u64 {
+            self.len() as u64
+        }
+    }
+    impl Sum for () {
+        fn sum(&self) -> u64 {
+            0
+        }
+    }
+
+    let wide = [1_u64, 2, 3, 4];
+    let text = "text";
+    let unit = ();
+    let ptrs = [
+        DynPtr::new(NonNull::from(&wide as &dyn Sum)),
+        DynPtr::new(NonNull::from(&text as &dyn Sum)),
+        DynPtr::new(NonNull::from(&unit as &dyn Sum)),
+    ];
+
+    // Safety: Each pointer refers to a live local that isn't mutated.
+    let sums = ptrs.map(|ptr| unsafe { ptr.cast::<dyn Sum>().unwrap().as_ref().sum() });
+    assert_eq!(sums, [10, 4, 0]);
+    let sizes = ptrs.map(|ptr| (ptr.value_size::<dyn Sum>(), ptr.value_align::<dyn Sum>()));
+    assert_eq!(
+        sizes,
+        [
+            (Some(size_of::<[u64; 4]>()), Some(align_of::<[u64; 4]>())),
+            (Some(size_of::<&str>()), Some(align_of::<&str>())),
+            (Some(0), Some(1)),
+        ]
+    );
+    for ptr in ptrs {
+        assert_ne!(ptr.addresses().1, 0);
+    }
+
+    static DROPPED: AtomicBool = AtomicBool::new(false);
+
+    struct SetOnDrop;
+
+    impl Drop for SetOnDrop {
+        fn drop(&mut self) {
+            DROPPED.store(true, Ordering::Relaxed);
+        }
+    }
+
+    trait Droppable {}
+    impl Droppable for SetOnDrop {}
+
+    let mut owned = ManuallyDrop::new(SetOnDrop);
+    let owned_ptr = DynPtr::new(NonNull::from(&mut *owned as &mut dyn Droppable));
+    // Safety: `ManuallyDrop` ensures the object won't be dropped again.
+    assert!(unsafe { owned_ptr.drop_in_place::<dyn Droppable>() });
+    assert!(DROPPED.load(Ordering::Relaxed));
+}
+
+#[test]
+fn negotiate_preferred_trait() {
+    trait Unsupported {}
+
+    let preferred = [
+        DynTypeId::of::<dyn Unsupported>(),
+        DynTypeId::of::<dyn WithMessage>(),
+    ];
+    let (chosen, obj) = Implementer.negotiate(&preferred).unwrap();
+    assert_eq!(chosen, DynTypeId::of::<dyn WithMessage>());
+    assert_eq!(
+        obj.cast::<dyn WithMessage>().unwrap().message(),
+        "hello from implementer"
+    );
+
+    // Forwarding impls negotiate on behalf of the object they refer to.
+    assert!(AsTraitObject::negotiate(&&Implementer, &preferred).is_some());
+    assert!(Implementer.negotiate(&preferred[..1]).is_none());
+    assert!(Implementer.negotiate(&[]).is_none());
+    assert!(NotImplementer.negotiate(&preferred).is_none());
+}
+
+#[test]
+fn typed_handles() {
+    use crate::{DynMut, DynTyped};
+
+    trait Counter {
+        fn bump(&mut self) -> u32;
+    }
+
+    struct Count(u32);
+
+    impl Counter for Count {
+        fn bump(&mut self) -> u32 {
+            self.0 += 1;
+            self.0
+        }
+    }
+
+    // Validate once at the boundary, then pass the proven handle inward.
+    fn inner(item: DynTyped<'_, dyn WithMessage>) -> &'static str {
+        item.get().message()
+    }
+
+    let erased = Dyn::new(&Implementer as &dyn WithMessage);
+    assert!(erased.typed::<dyn Counter>().is_none());
+    let typed = erased.typed::<dyn WithMessage>().unwrap();
+    assert_eq!(inner(typed), "hello from implementer");
+    assert_eq!(typed.as_dyn().dyn_type_id(), typed.dyn_type_id());
+
+    let mut count = Count(0);
+    let erased = DynMut::new(&mut count as &mut dyn Counter);
+    assert!(erased.typed::<dyn WithMessage>().is_none());
+    let mut typed = erased.typed::<dyn Counter>().unwrap();
+    assert_eq!(typed.get_mut().bump(), 1);
+    assert_eq!(typed.into_mut().bump(), 2);
+    assert_eq!(count.0, 2);
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn revocable_plugin_handle() {
+    use crate::{DynPtr, RevocableDyn};
+    use core::ptr::NonNull;
+
+    let plugin = Implementer;
+    let host = RevocableDyn::new(DynPtr::new(NonNull::from(&plugin as &dyn WithMessage)));
+    let handed_out = host.clone();
+
+    // Before revocation, every clone can cast as normal.
+    assert!(!handed_out.is_revoked());
+    let ptr = handed_out.cast::<dyn WithMessage>().unwrap();
+    // Safety: `plugin` is still live.
+    assert_eq!(unsafe { ptr.as_ref() }.message(), "hello from implementer");
+
+    // Unloading the plugin revokes all outstanding handles.
+    host.revoke();
+    assert!(handed_out.is_revoked());
+    assert!(handed_out.cast::<dyn WithMessage>().is_none());
+    assert!(host.cast::<dyn WithMessage>().is_none());
+    assert_eq!(handed_out.dyn_type_id(), DynTypeId::of::<dyn WithMessage>());
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn interned_handles_share_metadata() {
+    use crate::{DynPtr, InternedDynPtr};
+    use core::{fmt::Debug, ptr::NonNull};
+
+    let implementers = [Implementer, Implementer, Implementer];
+    let handles = implementers
+        .each_ref()
+        .map(|obj| InternedDynPtr::new(DynPtr::new(NonNull::from(obj as &dyn WithMessage))));
+    // Rust doesn't guarantee a single vtable per type and trait, and Miri
+    // deliberately generates a fresh one for each unsizing coercion.
+    #[cfg(not(miri))]
+    {
+        assert!(handles[0].shares_metadata_with(&handles[1]));
+        assert!(handles[1].shares_metadata_with(&handles[2]));
+    }
+    for (handle, obj) in handles.iter().zip(&implementers) {
+        let ptr = handle.cast::<dyn WithMessage>().unwrap();
+        assert!(core::ptr::addr_eq(ptr.as_ptr(), obj));
+        // Safety: `implementers` is still live.
+        assert_eq!(unsafe { ptr.as_ref() }.message(), "hello from implementer");
+        assert!(handle.cast::<dyn Debug>().is_none());
+    }
+
+    // A different concrete type behind the same trait gets its own entry.
+    let value = 1_u8;
+    let other = InternedDynPtr::new(DynPtr::new(NonNull::from(&value as &dyn Debug)));
+    assert!(!other.shares_metadata_with(&handles[0]));
+    assert_eq!(other.dyn_type_id(), DynTypeId::of::<dyn Debug>());
+}
+
+// The exported macros must not depend on, or interfere with, whatever names
+// happen to be in scope where they are expanded, including when they are
+// expanded from inside another macro.
+mod macro_hygiene {
+    use crate::traitcast::{AsTraitObject, match_dyn_type_id, match_dyn_type_id_mut};
+
+    // These deliberately collide with the names the macros need.
+    #[derive(Debug, PartialEq)]
+    struct Dyn;
+    #[allow(dead_code)]
+    struct DynTypeId;
+    #[allow(dead_code)]
+    enum Option {
+        Some,
+        None,
+    }
+
+    trait Named {
+        fn name(&self) -> &'static str;
+    }
+
+    impl Named for Dyn {
+        fn name(&self) -> &'static str {
+            "user's Dyn"
+        }
+    }
+
+    macro_rules! offer_named {
+        ($ty:ty) => {
+            impl AsTraitObject for $ty {
+                fn as_trait_object<'a>(
+                    &'a self,
+                    type_id: crate::DynTypeId,
+                ) -> ::core::option::Option<crate::Dyn<'a>> {
+                    match_dyn_type_id!(type_id => { Named => &Dyn })
+                }
+            }
+        };
+    }
+
+    struct Host;
+    offer_named!(Host);
+
+    macro_rules! offer_named_mut {
+        ($type_id:expr, $value:expr) => {
+            match_dyn_type_id_mut!($value, $type_id => Named)
+        };
+    }
+
+    #[test]
+    fn match_dyn_type_id_inside_user_macro() {
+        let obj = Host.as_trait_object(crate::DynTypeId::of::<dyn Named>());
+        assert_eq!(
+            obj.unwrap().cast::<dyn Named>().unwrap().name(),
+            "user's Dyn"
+        );
+
+        let mut value = Dyn;
+        let obj = offer_named_mut!(crate::DynTypeId::of::<dyn Named>(), &mut value);
+        assert_eq!(
+            obj.unwrap().cast::<dyn Named>().unwrap().name(),
+            "user's Dyn"
+        );
+    }
+}
+
+#[test]
+fn dyn_set_from_views() {
+    use crate::DynSet;
+
+    trait Shape {
+        fn sides(&self) -> u32;
+    }
+    trait Colored {
+        fn color(&self) -> &'static str;
+    }
+
+    struct Triangle;
+
+    impl Shape for Triangle {
+        fn sides(&self) -> u32 {
+            3
+        }
+    }
+
+    impl Colored for Triangle {
+        fn color(&self) -> &'static str {
+            "red"
+        }
+    }
+
+    impl WithMessage for Triangle {
+        fn message(&self) -> &'static str {
+            "hello from triangle"
+        }
+    }
+
+    let x = Triangle;
+    let set = DynSet::from_views((
+        &x as &dyn Shape,
+        &x as &dyn Colored,
+        Dyn::new(&x as &dyn WithMessage),
+    ));
+    assert_eq!(set.len(), 3);
+    assert_eq!(set.cast::<dyn Shape>().unwrap().sides(), 3);
+    assert_eq!(set.cast::<dyn Colored>().unwrap().color(), "red");
+    assert_eq!(
+        set.cast::<dyn WithMessage>().unwrap().message(),
+        "hello from triangle"
+    );
+    assert!(set.contains(DynTypeId::of::<dyn Colored>()));
+    assert!(set.cast::<dyn AsTraitObject>().is_none());
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn external_caster_reentrant_register() {
+    use crate::traitcast::ExternalCaster;
+    use core::fmt::{Debug, Display};
+
+    static CASTER: ExternalCaster = ExternalCaster::new();
+
+    // A caster that registers another cast the first time it runs, which
+    // must not deadlock on the registry's lock.
+    CASTER.register::<u32, dyn Debug, _>(|v| {
+        CASTER.register::<u32, dyn Display, _>(|v| v as &dyn Display);
+        v as &dyn Debug
+    });
+
+    let value = 5_u32;
+    assert!(CASTER.cast::<dyn Display>(&value).is_none());
+    assert!(CASTER.cast::<dyn Debug>(&value).is_some());
+    assert!(CASTER.cast::<dyn Display>(&value).is_some());
+}
+
+#[cfg(all(feature = "debug-validity", debug_assertions))]
+#[test]
+#[should_panic(expected = "generation")]
+fn stale_generation() {
+    use crate::{DynPtr, Generation};
+    use core::ptr::NonNull;
+
+    static GENERATION: Generation = Generation::new();
+
+    let obj = Implementer;
+    let ptr = DynPtr::new(NonNull::from(&obj as &dyn WithMessage)).with_generation(&GENERATION);
+    assert!(ptr.cast::<dyn WithMessage>().is_some());
+    GENERATION.advance();
+    ptr.cast::<dyn WithMessage>();
+}
+
+#[cfg(all(feature = "debug-validity", debug_assertions))]
+#[test]
+#[should_panic(expected = "generation")]
+fn borrowed_generation() {
+    use crate::Generation;
+
+    // A container that owns both its objects and the generation that
+    // tracks them.
+    struct Table {
+        generation: Generation,
+        obj: Implementer,
+    }
+
+    let table = Table {
+        generation: Generation::new(),
+        obj: Implementer,
+    };
+    let handle = Dyn::new(&table.obj as &dyn WithMessage).with_generation(&table.generation);
+    assert!(handle.cast::<dyn WithMessage>().is_some());
+    table.generation.advance();
+
+    // The raw pointer could outlive the borrow of the generation, so it
+    // doesn't carry the association and isn't checked.
+    assert!(handle.as_ptr().cast::<dyn WithMessage>().is_some());
+    handle.cast::<dyn WithMessage>();
+}
+
+#[test]
+#[cfg(all(
+    target_pointer_width = "64",
+    not(feature = "debug-diagnostics"),
+    not(all(feature = "debug-validity", debug_assertions))
+))]
+fn dyn_ptr_size() {
+    use crate::DynPtr;
+
+    // The data pointer, the vtable pointer, and the 128-bit TypeId.
+    assert_eq!(size_of::<DynPtr>(), 32);
+}
@@ -9,8 +9,22 @@
 //!
 //! If you'd like to learn more, start with [`AsTraitObject`].
 
+use core::{any::Any, cell::Ref, ops::Deref};
+
 use crate::{Dyn, DynMut, DynTypeId, TraitObject};
 
+#[cfg(feature = "std")]
+mod external;
+
+#[cfg(feature = "std")]
+pub use external::{ExternalCaster, cast_external, register_external};
+
+#[cfg(feature = "downcast-rs")]
+mod downcast;
+
+#[cfg(feature = "downcast-rs")]
+pub use downcast::as_trait_object_via_downcast;
+
 /// A `dyn`-compatible trait used by [`cast_trait_object`] to find out whether
 /// an implementer wishes to support casting to a trait object of a different
 /// type and, if so, to get a type-erased trait object for that trait.
@@ -27,6 +41,20 @@ use crate::{Dyn, DynMut, DynTypeId, TraitObject};
 /// works with trait objects of your own trait. This trait is here mainly
 /// just as an example of how to use this library's facilities to implement
 /// trait-to-trait casting.
+///
+/// Types must opt in to casting by implementing this trait, even if they
+/// don't offer any trait objects:
+///
+/// ```compile_fail,E0277
+/// # use any_dyn::traitcast::AsTraitObject;
+/// struct Forgotten;
+/// let objs: Vec<Box<dyn AsTraitObject>> = vec![Box::new(Forgotten)];
+/// ```
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` does not implement `AsTraitObject`",
+    label = "`{Self}` must implement `AsTraitObject` to support trait object casting",
+    note = "implement `AsTraitObject::as_trait_object` using `match_dyn_type_id!` to list the traits `{Self}` offers, or use an empty `impl` to offer none"
+)]
 pub trait AsTraitObject {
     /// Returns a type-erased trait object for the type identified by `type_id`
     /// if and only if the implementer wishes to offer an implementation of
@@ -43,6 +71,158 @@ pub trait AsTraitObject {
         let _ = type_id;
         None
     }
+
+    /// Returns an iterator over all of the trait objects the implementer
+    /// offers, along with the [`DynTypeId`] of each one.
+    ///
+    /// The default implementation returns an empty iterator, so callers
+    /// cannot rely on this to discover every trait object that
+    /// [`AsTraitObject::as_trait_object`] would return. Implementations can
+    /// use [`trait_objects`] to generate the result from the same list of
+    /// traits given to [`match_dyn_type_id`].
+    #[inline]
+    fn trait_objects<'a>(&'a self) -> TraitObjects<'a> {
+        TraitObjects::empty()
+    }
+
+    /// Returns the first trait object from `preferred` that the implementer
+    /// offers, along with the [`DynTypeId`] it was matched against.
+    ///
+    /// This allows a caller that can work with any of several traits to
+    /// state its order of preference and let the implementer choose, without
+    /// the caller needing to probe each trait in turn itself.
+    ///
+    /// The default implementation tries each entry in order using
+    /// [`AsTraitObject::as_trait_object`], and so implementations written
+    /// using [`match_dyn_type_id`] get a suitable implementation for free.
+    /// It returns `None` if none of the given traits are offered, including
+    /// when `preferred` is empty.
+    ///
+    /// ```
+    /// # use any_dyn::traitcast::{match_dyn_type_id, AsTraitObject};
+    /// # use any_dyn::{Dyn, DynTypeId};
+    /// trait Json {}
+    /// trait Text {}
+    /// struct Report;
+    /// impl Text for Report {}
+    /// impl AsTraitObject for Report {
+    ///     fn as_trait_object<'a>(&'a self, type_id: DynTypeId) -> Option<Dyn<'a>> {
+    ///         match_dyn_type_id!(self, type_id => Text)
+    ///     }
+    /// }
+    ///
+    /// let preferred = [DynTypeId::of::<dyn Json>(), DynTypeId::of::<dyn Text>()];
+    /// let (chosen, obj) = Report.negotiate(&preferred).unwrap();
+    /// assert_eq!(chosen, DynTypeId::of::<dyn Text>());
+    /// assert!(obj.cast::<dyn Text>().is_some());
+    /// ```
+    fn negotiate<'a>(&'a self, preferred: &[DynTypeId]) -> Option<(DynTypeId, Dyn<'a>)> {
+        preferred
+            .iter()
+            .find_map(|&type_id| Some((type_id, self.as_trait_object(type_id)?)))
+    }
+}
+
+impl<T: AsTraitObject + ?Sized> AsTraitObject for &T {
+    #[inline]
+    fn as_trait_object<'a>(&'a self, type_id: DynTypeId) -> Option<Dyn<'a>> {
+        (**self).as_trait_object(type_id)
+    }
+
+    #[inline]
+    fn trait_objects<'a>(&'a self) -> TraitObjects<'a> {
+        (**self).trait_objects()
+    }
+
+    #[inline]
+    fn negotiate<'a>(&'a self, preferred: &[DynTypeId]) -> Option<(DynTypeId, Dyn<'a>)> {
+        (**self).negotiate(preferred)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T: AsTraitObject + ?Sized> AsTraitObject for alloc::boxed::Box<T> {
+    #[inline]
+    fn as_trait_object<'a>(&'a self, type_id: DynTypeId) -> Option<Dyn<'a>> {
+        (**self).as_trait_object(type_id)
+    }
+
+    #[inline]
+    fn trait_objects<'a>(&'a self) -> TraitObjects<'a> {
+        (**self).trait_objects()
+    }
+
+    #[inline]
+    fn negotiate<'a>(&'a self, preferred: &[DynTypeId]) -> Option<(DynTypeId, Dyn<'a>)> {
+        (**self).negotiate(preferred)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T: AsTraitObject + ?Sized> AsTraitObject for alloc::rc::Rc<T> {
+    #[inline]
+    fn as_trait_object<'a>(&'a self, type_id: DynTypeId) -> Option<Dyn<'a>> {
+        (**self).as_trait_object(type_id)
+    }
+
+    #[inline]
+    fn trait_objects<'a>(&'a self) -> TraitObjects<'a> {
+        (**self).trait_objects()
+    }
+
+    #[inline]
+    fn negotiate<'a>(&'a self, preferred: &[DynTypeId]) -> Option<(DynTypeId, Dyn<'a>)> {
+        (**self).negotiate(preferred)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T: AsTraitObject + ?Sized> AsTraitObject for alloc::sync::Arc<T> {
+    #[inline]
+    fn as_trait_object<'a>(&'a self, type_id: DynTypeId) -> Option<Dyn<'a>> {
+        (**self).as_trait_object(type_id)
+    }
+
+    #[inline]
+    fn trait_objects<'a>(&'a self) -> TraitObjects<'a> {
+        (**self).trait_objects()
+    }
+
+    #[inline]
+    fn negotiate<'a>(&'a self, preferred: &[DynTypeId]) -> Option<(DynTypeId, Dyn<'a>)> {
+        (**self).negotiate(preferred)
+    }
+}
+
+/// The mutable counterpart of [`AsTraitObject`], for implementers that wish
+/// to offer mutable trait objects.
+///
+/// Implementations can typically use [`match_dyn_type_id_mut`] to perform the
+/// appropriate type matching and [`DynMut`] construction.
+pub trait AsTraitObjectMut {
+    /// Returns a type-erased mutable trait object for the type identified by
+    /// `type_id` if and only if the implementer wishes to offer an
+    /// implementation of the associated trait.
+    #[inline]
+    fn as_trait_object_mut<'a>(&'a mut self, type_id: DynTypeId) -> Option<DynMut<'a>> {
+        let _ = type_id;
+        None
+    }
+}
+
+impl<T: AsTraitObjectMut + ?Sized> AsTraitObjectMut for &mut T {
+    #[inline]
+    fn as_trait_object_mut<'a>(&'a mut self, type_id: DynTypeId) -> Option<DynMut<'a>> {
+        (**self).as_trait_object_mut(type_id)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T: AsTraitObjectMut + ?Sized> AsTraitObjectMut for alloc::boxed::Box<T> {
+    #[inline]
+    fn as_trait_object_mut<'a>(&'a mut self, type_id: DynTypeId) -> Option<DynMut<'a>> {
+        (**self).as_trait_object_mut(type_id)
+    }
 }
 
 /// Dynamically cast any [`AsTraitObject`] implementer to an arbitrary trait
@@ -81,6 +261,17 @@ pub trait AsTraitObject {
 /// }
 /// ```
 ///
+/// [`AsTraitObject`] is also implemented for references and, when the `alloc`
+/// feature is enabled, for `Box`, `Rc`, and `Arc`, forwarding to the
+/// referenced object. A reference to one of those smart pointers can
+/// therefore be passed directly, without explicitly dereferencing it first.
+/// The result then borrows from the smart pointer.
+///
+/// Trait objects with additional auto traits, such as
+/// `dyn AsTraitObject + Send + Sync`, also coerce to `&dyn AsTraitObject`
+/// automatically. The result is still exactly `&Dyn`, without any of the
+/// auto traits from the original trait object.
+///
 /// Refer to [`AsTraitObject`] for more information. This is really just a
 /// thin wrapper around [`AsTraitObject::as_trait_object`] followed by calling
 /// [`Dyn::cast`] on its result, but typically more convenient to use because
@@ -94,140 +285,618 @@ pub fn cast_trait_object<Dyn: TraitObject + ?Sized + 'static>(
     any.cast::<Dyn>()
 }
 
-#[doc(hidden)]
-#[macro_export]
-macro_rules! __match_dyn_type_id {
-    ($self:expr, $type_id:expr => $($trait_n:path),+ ) => {{
-        use $crate::{DynTypeId, Dyn};
-        let type_id: DynTypeId = $type_id;
-        let _: &_ = $self;
-        let ret: Option<Dyn> = if false {
-            _ = type_id;
-            None
-        }
-        $(
-        else if type_id == DynTypeId::of::<dyn $trait_n>() {
-            Some(Dyn::new($self as &dyn $trait_n))
-        }
-        )+
-        else {
-            None
-        };
-        ret
-    }};
+/// The mutable counterpart of [`cast_trait_object`], for [`AsTraitObjectMut`]
+/// implementers.
+///
+/// ```
+/// # use any_dyn::{
+/// #     DynMut,
+/// #     DynTypeId,
+/// #     traitcast::{
+/// #         AsTraitObjectMut,
+/// #         cast_trait_object_mut,
+/// #         match_dyn_type_id_mut,
+/// #     },
+/// # };
+/// # trait SomeTrait { fn some_trait_method(&mut self); }
+/// # struct SomeStruct { calls: usize }
+/// # impl SomeTrait for SomeStruct { fn some_trait_method(&mut self) { self.calls += 1; } }
+/// # impl AsTraitObjectMut for SomeStruct {
+/// #     fn as_trait_object_mut<'a>(&'a mut self, type_id: DynTypeId) -> Option<DynMut<'a>> {
+/// #         match_dyn_type_id_mut!(self, type_id => SomeTrait)
+/// #     }
+/// # }
+/// #
+/// let mut concrete = SomeStruct { calls: 0 };
+/// let as_trait_object = &mut concrete as &mut dyn AsTraitObjectMut;
+/// //
+/// // ...then store `as_trait_object` somewhere that "forgets" its original
+/// // concrete type, and then subsequently pull it back out and...
+/// //
+/// if let Some(trait_obj) = cast_trait_object_mut::<dyn SomeTrait>(as_trait_object) {
+///     // Call SomeTrait::some_trait_method only if the type chose to
+///     // allow casting to that trait.
+///     trait_obj.some_trait_method();
+/// }
+/// # assert_eq!(concrete.calls, 1);
+/// ```
+///
+/// The result exclusively borrows `obj` for as long as it's in use, so `obj`
+/// cannot be used again, even for another cast, until the result is no
+/// longer needed.
+#[inline]
+pub fn cast_trait_object_mut<Dyn: TraitObject + ?Sized + 'static>(
+    obj: &mut dyn AsTraitObjectMut,
+) -> Option<&mut Dyn> {
+    let any = obj.as_trait_object_mut(DynTypeId::of::<Dyn>())?;
+    any.cast::<Dyn>()
 }
 
-#[doc(hidden)]
-#[macro_export]
-macro_rules! __match_dyn_type_id_mut {
-    ($self:expr, $type_id:expr => $($trait_n:path),+ ) => {{
-        use $crate::{DynTypeId, DynMut};
-        let type_id: DynTypeId = $type_id;
-        let _: &_ = $self;
-        let ret: Option<DynMut> = if false {
-            _ = type_id;
-            None
+/// Like [`cast_trait_object`], but if `obj` doesn't offer `Dyn` directly then
+/// also tries any [`AsTraitObject`] implementation it offers, up to
+/// `max_depth` levels of indirection.
+///
+/// This is intended for layered or proxy handles, which offer the object
+/// they wrap as `dyn AsTraitObject` rather than forwarding each request
+/// themselves. At each level, the object is first asked for `Dyn` and only
+/// if it doesn't offer that is it asked for `dyn AsTraitObject`, whose
+/// result then becomes the object for the next level.
+///
+/// A `max_depth` of zero behaves exactly like [`cast_trait_object`]. The
+/// limit ensures that cyclic chains of proxies cannot cause an infinite loop.
+///
+/// Every object in the chain is borrowed from the previous one, so the
+/// result can borrow from `obj` for only as long as `obj` itself is
+/// borrowed. As with any other trait object type, an object can be offered
+/// as `dyn AsTraitObject` only if its type is `'static`, so a proxy
+/// typically owns the object it wraps or holds a `'static` reference to it.
+///
+/// ```
+/// # use any_dyn::{Dyn, DynTypeId, traitcast::{AsTraitObject, cast_trait_object_recursive, match_dyn_type_id}};
+/// # use core::fmt::Display;
+/// struct Inner(u32);
+///
+/// impl AsTraitObject for Inner {
+///     fn as_trait_object<'a>(&'a self, type_id: DynTypeId) -> Option<Dyn<'a>> {
+///         match_dyn_type_id!(&self.0, type_id => Display)
+///     }
+/// }
+///
+/// struct Proxy<T>(T);
+///
+/// impl<T: AsTraitObject + 'static> AsTraitObject for Proxy<T> {
+///     fn as_trait_object<'a>(&'a self, type_id: DynTypeId) -> Option<Dyn<'a>> {
+///         match_dyn_type_id!(&self.0, type_id => AsTraitObject)
+///     }
+/// }
+///
+/// let outer = Proxy(Proxy(Inner(4)));
+/// assert!(cast_trait_object_recursive::<dyn Display>(&outer, 1).is_none());
+/// let display = cast_trait_object_recursive::<dyn Display>(&outer, 2).unwrap();
+/// assert_eq!(display.to_string(), "4");
+/// ```
+pub fn cast_trait_object_recursive<Dyn: TraitObject + ?Sized + 'static>(
+    obj: &dyn AsTraitObject,
+    max_depth: usize,
+) -> Option<&Dyn> {
+    let mut obj = obj;
+    for _ in 0..max_depth {
+        if let Some(found) = cast_trait_object::<Dyn>(obj) {
+            return Some(found);
         }
-        $(
-        else if type_id == DynTypeId::of::<dyn $trait_n>() {
-            Some(DynMut::new($self as &mut dyn $trait_n))
+        obj = cast_trait_object::<dyn AsTraitObject>(obj)?;
+    }
+    cast_trait_object::<Dyn>(obj)
+}
+
+/// Like [`cast_trait_object`], but returns a [`CastFailure`] describing why
+/// the cast failed.
+///
+/// This is intended as a debugging aid for implementations of
+/// [`AsTraitObject`]: a correct implementation never returns a trait object
+/// of a different type than the one requested, but a buggy one might, and
+/// [`cast_trait_object`] can't distinguish that from the implementer not
+/// offering the requested trait at all.
+///
+/// ```
+/// # use any_dyn::{Dyn, DynTypeId, traitcast::{AsTraitObject, CastFailure, cast_trait_object_diagnostic}};
+/// # use core::fmt::{Debug, Display};
+/// struct Buggy(u32);
+///
+/// impl AsTraitObject for Buggy {
+///     fn as_trait_object<'a>(&'a self, type_id: DynTypeId) -> Option<Dyn<'a>> {
+///         // Oops: this returns a dyn Debug even when asked for dyn Display.
+///         if type_id == DynTypeId::of::<dyn Display>() {
+///             return Some(Dyn::new(&self.0 as &dyn Debug));
+///         }
+///         None
+///     }
+/// }
+///
+/// let obj = Buggy(5);
+/// assert!(matches!(
+///     cast_trait_object_diagnostic::<dyn Debug>(&obj),
+///     Err(CastFailure::Unsupported),
+/// ));
+/// assert!(matches!(
+///     cast_trait_object_diagnostic::<dyn Display>(&obj),
+///     Err(CastFailure::InconsistentImpl { .. }),
+/// ));
+/// ```
+pub fn cast_trait_object_diagnostic<Dyn: TraitObject + ?Sized + 'static>(
+    obj: &dyn AsTraitObject,
+) -> Result<&Dyn, CastFailure> {
+    let requested = DynTypeId::of::<Dyn>();
+    let any = obj
+        .as_trait_object(requested)
+        .ok_or(CastFailure::Unsupported)?;
+    any.cast::<Dyn>().ok_or(CastFailure::InconsistentImpl {
+        offered: any.dyn_type_id(),
+        requested,
+    })
+}
+
+/// The reason for a failure reported by [`cast_trait_object_diagnostic`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CastFailure {
+    /// The implementer does not offer the requested trait object type.
+    Unsupported,
+
+    /// The implementer returned a trait object of a different type than the
+    /// one requested, which indicates a bug in its implementation of
+    /// [`AsTraitObject::as_trait_object`].
+    InconsistentImpl {
+        /// The trait object type the implementer actually returned.
+        offered: DynTypeId,
+        /// The trait object type that was requested.
+        requested: DynTypeId,
+    },
+}
+
+impl core::fmt::Display for CastFailure {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Unsupported => f.write_str("trait object type is not supported"),
+            Self::InconsistentImpl { .. } => {
+                f.write_str("as_trait_object returned a trait object of the wrong type")
+            }
         }
-        )+
-        else {
-            None
-        };
-        ret
-    }};
+    }
 }
 
-/// Helper for implementing lookups from [`DynTypeId`] to [`Dyn`] for
-/// a specified set of traits.
+impl core::error::Error for CastFailure {}
+
+/// Casts the given [`AsTraitObject`] implementer to the trait object type
+/// `Source` and then, if successful, returns the result of passing it to `f`.
 ///
-/// Implementations of [`AsTraitObject::as_trait_object`], or of any similar
-/// method of your own trait that supports dynamic trait object casting,
-/// can use this to concisely declare which traits they intend to support.
+/// This is a shorthand for `cast_trait_object::<Source>(obj).map(f)`, for
+/// situations where the caller needs some value derived from the trait object
+/// rather than the trait object itself. The trait object passed to `f` has
+/// the same lifetime as `obj`, so `f` can return something borrowed from it.
 ///
 /// ```
-/// use any_dyn::traitcast::{AsTraitObject, match_dyn_type_id};
-/// use any_dyn::{Dyn, DynTypeId};
+/// # use any_dyn::{
+/// #     Dyn,
+/// #     DynTypeId,
+/// #     traitcast::{AsTraitObject, cast_and_map, match_dyn_type_id},
+/// # };
+/// # trait WithName { fn name(&self) -> &str; }
+/// # struct Named(String);
+/// # impl WithName for Named { fn name(&self) -> &str { &self.0 } }
+/// # impl AsTraitObject for Named {
+/// #     fn as_trait_object<'a>(&'a self, type_id: DynTypeId) -> Option<Dyn<'a>> {
+/// #         match_dyn_type_id!(self, type_id => WithName)
+/// #     }
+/// # }
+/// let named = Named("example".to_string());
+/// let first_char = cast_and_map::<dyn WithName, _>(&named, |n| n.name().chars().next());
+/// assert_eq!(first_char, Some(Some('e')));
+/// ```
+#[inline]
+pub fn cast_and_map<'a, Source: TraitObject + ?Sized + 'static, T>(
+    obj: &'a dyn AsTraitObject,
+    f: impl FnOnce(&'a Source) -> T,
+) -> Option<T> {
+    cast_trait_object::<Source>(obj).map(f)
+}
+
+/// Casts the given [`AsTraitObject`] implementer to `dyn Any` and then, if
+/// successful, downcasts the result to the concrete type `T`.
 ///
-/// trait SomeTrait { /* ... */ }
-/// trait SomeOtherTrait { /* ... */ }
+/// This succeeds only if the implementer offers `dyn Any` as one of its trait
+/// object types, such as by including `dyn Any` in its [`match_dyn_type_id`]
+/// list, and `T` is the concrete type of the object it returns for that
+/// trait object type.
 ///
-/// struct SomeStruct { /* ... */ }
-/// impl SomeTrait for SomeStruct { /* ... */ }
-/// impl SomeOtherTrait for SomeStruct { /* ... */ }
+/// ```
+/// # use any_dyn::{
+/// #     Dyn,
+/// #     DynTypeId,
+/// #     traitcast::{AsTraitObject, cast_then_downcast, match_dyn_type_id},
+/// # };
+/// # use core::any::Any;
+/// # trait WithName { fn name(&self) -> &str; }
+/// struct Named(String);
+/// # impl WithName for Named { fn name(&self) -> &str { &self.0 } }
 ///
-/// impl AsTraitObject for SomeStruct {
+/// impl AsTraitObject for Named {
 ///     fn as_trait_object<'a>(&'a self, type_id: DynTypeId) -> Option<Dyn<'a>> {
-///         // The macro expands to an expression that returns Option<Dyn>.
-///         match_dyn_type_id!(self, type_id => SomeTrait, SomeOtherTrait)
+///         match_dyn_type_id!(self, type_id => WithName, Any)
 ///     }
 /// }
+///
+/// let handle: Box<dyn AsTraitObject> = Box::new(Named("example".to_string()));
+/// let named = cast_then_downcast::<Named>(&*handle).unwrap();
+/// assert_eq!(named.0, "example");
+/// assert!(cast_then_downcast::<String>(&*handle).is_none());
 /// ```
+#[inline]
+pub fn cast_then_downcast<T: Any>(obj: &dyn AsTraitObject) -> Option<&T> {
+    cast_trait_object::<dyn Any>(obj)?.downcast_ref::<T>()
+}
+
+/// Casts each element of the given slice that offers the trait object type
+/// `Dyn` through [`AsTraitObjectMut`], returning mutable references to all of
+/// the results in their original order.
 ///
-/// `self` must be a reference to a type that implements all of the listed
-/// traits, all of which be `dyn`-compatible and `'static`. `type_id` must
-/// be a value of type `DynTypeId`.
+/// Elements that don't offer `Dyn` are skipped. Each element is borrowed
+/// exclusively by at most one of the results, so the results can all be used
+/// at once for batch mutation.
 ///
-/// The generated code is essentially just a chain of `if`/`else if`/`else`
-/// statements comparing the given `type_id` with each of the listed traits
-/// in turn. For example, the `match_dyn_type_id!` call in the above expands
-/// to something functionally equivalent to the following:
+/// This is available only when the `alloc` feature is enabled.
+#[cfg(feature = "alloc")]
+pub fn cast_all_mut_vec<Dyn: TraitObject + ?Sized + 'static>(
+    slice: &mut [alloc::boxed::Box<dyn AsTraitObjectMut>],
+) -> alloc::vec::Vec<&mut Dyn> {
+    slice
+        .iter_mut()
+        .filter_map(|obj| cast_trait_object_mut::<Dyn>(obj))
+        .collect()
+}
+
+/// Returns `true` if the given [`AsTraitObject`] implementer does _not_ offer
+/// an implementation of the trait object type `Dyn`.
+///
+/// This is equivalent to `cast_trait_object::<Dyn>(obj).is_none()`, but can
+/// make the intent clearer in code where the absence of a capability is the
+/// important property, such as when asserting that a handle cannot be used
+/// for a dangerous operation.
 ///
 /// ```
-/// # use any_dyn::{Dyn, DynTypeId};
-/// # trait SomeTrait {}
-/// # trait SomeOtherTrait {}
-/// # struct SomeStruct {}
-/// # impl SomeTrait for SomeStruct {}
-/// # impl SomeOtherTrait for SomeStruct {}
-/// # impl SomeStruct {
-/// # fn example<'a>(&'a self, type_id: DynTypeId) -> Option<Dyn<'a>> {
-/// if type_id == DynTypeId::of::<dyn SomeTrait>() {
-///     Some(Dyn::new(self as &dyn SomeTrait))
-/// } else if type_id == DynTypeId::of::<dyn SomeOtherTrait>() {
-///     Some(Dyn::new(self as &dyn SomeOtherTrait))
-/// } else {
-///     None
-/// }
-/// # }
-/// # }
+/// # use any_dyn::traitcast::{AsTraitObject, lacks_trait_object};
+/// # trait Dangerous {}
+/// # struct Harmless;
+/// # impl AsTraitObject for Harmless {}
+/// let handle = Harmless;
+/// assert!(lacks_trait_object::<dyn Dangerous>(&handle));
 /// ```
-///
-/// You are welcome to hand-write similar code yourself if you prefer. This
-/// macro is just a convenience helper to help focus on just listing which
-/// traits are supported, rather than exposing the implementation details.
-#[doc(inline)]
-pub use __match_dyn_type_id as match_dyn_type_id;
+#[inline]
+pub fn lacks_trait_object<Dyn: TraitObject + ?Sized + 'static>(obj: &dyn AsTraitObject) -> bool {
+    cast_trait_object::<Dyn>(obj).is_none()
+}
 
-/// Helper for implementing lookups from [`DynTypeId`] to [`DynMut`] for
-/// a specified set of traits.
+/// Probes each of the given handles for each of the given trait object types,
+/// returning a table of which handles offer which traits.
 ///
-/// This is just like [`match_dyn_type_id`] except that it produces
-/// [`DynMut`] results instead of [`Dyn`], and must therefore be used
-/// with a mutable reference to something that implements each of the listed
-/// traits.
+/// The result has one row per handle and one column per trait, in the same
+/// order as the arguments. An entry is `true` only if the handle offered a
+/// trait object of exactly the requested type, so an incorrect
+/// [`AsTraitObject`] implementation that offers a different type is reported
+/// as not supporting the trait. [`cast_trait_object_diagnostic`] can
+/// distinguish that case.
+///
+/// This is intended as a debugging aid for understanding how a set of handles
+/// will behave when dispatched, and is not optimized for speed.
 ///
 /// ```
-/// # use any_dyn::traitcast::match_dyn_type_id_mut;
-/// # use any_dyn::{DynMut, DynTypeId};
-/// # trait SomeTrait { /* ... */ }
-/// # trait SomeOtherTrait { /* ... */ }
-/// # struct SomeStruct { /* ... */ }
-/// # impl SomeTrait for SomeStruct { /* ... */ }
-/// # impl SomeOtherTrait for SomeStruct { /* ... */ }
-/// # trait AsTraitObjectMut {
-/// #   fn as_trait_object_mut<'a>(&'a mut self, type_id: DynTypeId) -> Option<DynMut<'a>>;
-/// # }
-/// // (AsTraitObjectMut is not a real trait in this library: it's just an
-/// // example of a hypothetical mutable-trait-object trait you could
-/// // specify yourself if you need something like it, and then implement it
-/// // with the help of this macro.)
-/// impl AsTraitObjectMut for SomeStruct {
-///     fn as_trait_object_mut<'a>(&'a mut self, type_id: DynTypeId) -> Option<DynMut<'a>> {
-///         // The macro expands to an expression that returns Option<DynMut>.
-///         match_dyn_type_id_mut!(self, type_id => SomeTrait, SomeOtherTrait)
+/// # use any_dyn::{Dyn, DynTypeId, traitcast::{AsTraitObject, capability_matrix, match_dyn_type_id}};
+/// # use core::fmt::{Debug, Display};
+/// struct Both(u8);
+/// struct Neither;
+///
+/// impl AsTraitObject for Both {
+///     fn as_trait_object<'a>(&'a self, type_id: DynTypeId) -> Option<Dyn<'a>> {
+///         match_dyn_type_id!(&self.0, type_id => Debug, Display)
+///     }
+/// }
+/// impl AsTraitObject for Neither {}
+///
+/// let matrix = capability_matrix(
+///     &[&Both(1), &Neither],
+///     &[DynTypeId::of::<dyn Debug>(), DynTypeId::of::<dyn Display>()],
+/// );
+/// assert_eq!(matrix, [[true, true], [false, false]]);
+/// ```
+///
+/// This is available only when the `alloc` feature is enabled.
+#[cfg(feature = "alloc")]
+pub fn capability_matrix(
+    handles: &[&dyn AsTraitObject],
+    traits: &[DynTypeId],
+) -> alloc::vec::Vec<alloc::vec::Vec<bool>> {
+    handles
+        .iter()
+        .map(|handle| {
+            traits
+                .iter()
+                .map(|&type_id| {
+                    handle
+                        .as_trait_object(type_id)
+                        .is_some_and(|obj| obj.dyn_type_id() == type_id)
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Like [`cast_trait_object`], but for an [`AsTraitObject`] implementer
+/// reached through a guard object, such as a lock guard.
+///
+/// The result borrows from the guard, so it cannot outlive the lock.
+///
+/// ```
+/// # use any_dyn::{
+/// #     Dyn,
+/// #     DynTypeId,
+/// #     traitcast::{AsTraitObject, cast_locked, match_dyn_type_id},
+/// # };
+/// # use std::sync::Mutex;
+/// # trait SomeTrait { fn some_trait_method(&self) {} }
+/// # struct SomeStruct {}
+/// # impl SomeTrait for SomeStruct {}
+/// # impl AsTraitObject for SomeStruct {
+/// #     fn as_trait_object<'a>(&'a self, type_id: DynTypeId) -> Option<Dyn<'a>> {
+/// #         match_dyn_type_id!(self, type_id => SomeTrait)
+/// #     }
+/// # }
+/// let registry: Mutex<SomeStruct> = Mutex::new(SomeStruct {});
+///
+/// let guard = registry.lock().unwrap();
+/// if let Some(trait_obj) = cast_locked::<dyn SomeTrait, _>(&guard) {
+///     trait_obj.some_trait_method();
+/// }
+/// ```
+#[inline]
+pub fn cast_locked<Dyn, G>(guard: &G) -> Option<&Dyn>
+where
+    Dyn: TraitObject + ?Sized + 'static,
+    G: Deref,
+    G::Target: AsTraitObject,
+{
+    let any = (**guard).as_trait_object(DynTypeId::of::<Dyn>())?;
+    any.cast::<Dyn>()
+}
+
+/// Like [`cast_trait_object`], but for an [`AsTraitObject`] implementer
+/// borrowed from a [`RefCell`](core::cell::RefCell).
+///
+/// The result is a [`Ref`] for the requested trait object type, which keeps
+/// the [`RefCell`](core::cell::RefCell) borrowed for as long as it's in use.
+///
+/// ```
+/// # use any_dyn::{
+/// #     Dyn,
+/// #     DynTypeId,
+/// #     traitcast::{AsTraitObject, cast_ref_cell, match_dyn_type_id},
+/// # };
+/// # use std::{cell::RefCell, rc::Rc};
+/// # trait SomeTrait { fn some_trait_method(&self) {} }
+/// # struct SomeStruct {}
+/// # impl SomeTrait for SomeStruct {}
+/// # impl AsTraitObject for SomeStruct {
+/// #     fn as_trait_object<'a>(&'a self, type_id: DynTypeId) -> Option<Dyn<'a>> {
+/// #         match_dyn_type_id!(self, type_id => SomeTrait)
+/// #     }
+/// # }
+/// let shared: Rc<RefCell<dyn AsTraitObject>> = Rc::new(RefCell::new(SomeStruct {}));
+///
+/// if let Some(trait_obj) = cast_ref_cell::<dyn SomeTrait, _>(shared.borrow()) {
+///     trait_obj.some_trait_method();
+/// }
+/// ```
+#[inline]
+pub fn cast_ref_cell<'b, Dyn, T>(r: Ref<'b, T>) -> Option<Ref<'b, Dyn>>
+where
+    Dyn: TraitObject + ?Sized + 'static,
+    T: AsTraitObject + ?Sized,
+{
+    Ref::filter_map(r, |obj| {
+        obj.as_trait_object(DynTypeId::of::<Dyn>())?.cast::<Dyn>()
+    })
+    .ok()
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __match_dyn_type_id {
+    ($type_id:expr => { $($trait_n:path => $value:expr),+ $(,)? }) => {{
+        let type_id: $crate::DynTypeId = $type_id;
+        let ret: ::core::option::Option<$crate::Dyn<'_>> = if false {
+            _ = type_id;
+            ::core::option::Option::None
+        }
+        $(
+        else if type_id == $crate::DynTypeId::of::<dyn $trait_n>() {
+            ::core::option::Option::Some($crate::Dyn::new($value as &dyn $trait_n))
+        }
+        )+
+        else {
+            ::core::option::Option::None
+        };
+        ret
+    }};
+    ($self:expr, $type_id:expr => $($trait_n:path),+ ) => {{
+        let type_id: $crate::DynTypeId = $type_id;
+        let _: &_ = $self;
+        let ret: ::core::option::Option<$crate::Dyn<'_>> = if false {
+            _ = type_id;
+            ::core::option::Option::None
+        }
+        $(
+        else if type_id == $crate::DynTypeId::of::<dyn $trait_n>() {
+            ::core::option::Option::Some($crate::Dyn::new($self as &dyn $trait_n))
+        }
+        )+
+        else {
+            ::core::option::Option::None
+        };
+        ret
+    }};
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __match_dyn_type_id_mut {
+    ($type_id:expr => { $($trait_n:path => $value:expr),+ $(,)? }) => {{
+        let type_id: $crate::DynTypeId = $type_id;
+        let ret: ::core::option::Option<$crate::DynMut<'_>> = if false {
+            _ = type_id;
+            ::core::option::Option::None
+        }
+        $(
+        else if type_id == $crate::DynTypeId::of::<dyn $trait_n>() {
+            ::core::option::Option::Some($crate::DynMut::new($value as &mut dyn $trait_n))
+        }
+        )+
+        else {
+            ::core::option::Option::None
+        };
+        ret
+    }};
+    ($self:expr, $type_id:expr => $($trait_n:path),+ ) => {{
+        let type_id: $crate::DynTypeId = $type_id;
+        let _: &_ = $self;
+        let ret: ::core::option::Option<$crate::DynMut<'_>> = if false {
+            _ = type_id;
+            ::core::option::Option::None
+        }
+        $(
+        else if type_id == $crate::DynTypeId::of::<dyn $trait_n>() {
+            ::core::option::Option::Some($crate::DynMut::new($self as &mut dyn $trait_n))
+        }
+        )+
+        else {
+            ::core::option::Option::None
+        };
+        ret
+    }};
+}
+
+/// Helper for implementing lookups from [`DynTypeId`] to [`Dyn`] for
+/// a specified set of traits.
+///
+/// Implementations of [`AsTraitObject::as_trait_object`], or of any similar
+/// method of your own trait that supports dynamic trait object casting,
+/// can use this to concisely declare which traits they intend to support.
+///
+/// ```
+/// use any_dyn::traitcast::{AsTraitObject, match_dyn_type_id};
+/// use any_dyn::{Dyn, DynTypeId};
+///
+/// trait SomeTrait { /* ... */ }
+/// trait SomeOtherTrait { /* ... */ }
+///
+/// struct SomeStruct { /* ... */ }
+/// impl SomeTrait for SomeStruct { /* ... */ }
+/// impl SomeOtherTrait for SomeStruct { /* ... */ }
+///
+/// impl AsTraitObject for SomeStruct {
+///     fn as_trait_object<'a>(&'a self, type_id: DynTypeId) -> Option<Dyn<'a>> {
+///         // The macro expands to an expression that returns Option<Dyn>.
+///         match_dyn_type_id!(self, type_id => SomeTrait, SomeOtherTrait)
+///     }
+/// }
+/// ```
+///
+/// `self` must be a reference to a type that implements all of the listed
+/// traits, all of which be `dyn`-compatible and `'static`. `type_id` must
+/// be a value of type `DynTypeId`.
+///
+/// The generated code is essentially just a chain of `if`/`else if`/`else`
+/// statements comparing the given `type_id` with each of the listed traits
+/// in turn. For example, the `match_dyn_type_id!` call in the above expands
+/// to something functionally equivalent to the following:
+///
+/// ```
+/// # use any_dyn::{Dyn, DynTypeId};
+/// # trait SomeTrait {}
+/// # trait SomeOtherTrait {}
+/// # struct SomeStruct {}
+/// # impl SomeTrait for SomeStruct {}
+/// # impl SomeOtherTrait for SomeStruct {}
+/// # impl SomeStruct {
+/// # fn example<'a>(&'a self, type_id: DynTypeId) -> Option<Dyn<'a>> {
+/// if type_id == DynTypeId::of::<dyn SomeTrait>() {
+///     Some(Dyn::new(self as &dyn SomeTrait))
+/// } else if type_id == DynTypeId::of::<dyn SomeOtherTrait>() {
+///     Some(Dyn::new(self as &dyn SomeOtherTrait))
+/// } else {
+///     None
+/// }
+/// # }
+/// # }
+/// ```
+///
+/// If producing the trait object requires some work first, such as lazy
+/// initialization, or if it should come from somewhere other than `self`,
+/// the macro also accepts a braced list of arms that each give an expression
+/// producing the reference for that trait. Each expression is evaluated only
+/// if its trait is the one requested:
+///
+/// ```
+/// # use any_dyn::traitcast::{AsTraitObject, cast_trait_object, match_dyn_type_id};
+/// # use any_dyn::{Dyn, DynTypeId};
+/// # use std::cell::OnceCell;
+/// # trait SomeTrait { fn some_trait_method(&self) -> u32; }
+/// # trait SomeOtherTrait {}
+/// # struct Index(u32);
+/// # impl SomeTrait for Index { fn some_trait_method(&self) -> u32 { self.0 } }
+/// # impl SomeOtherTrait for SomeStruct {}
+/// struct SomeStruct {
+///     index: OnceCell<Index>,
+/// }
+///
+/// impl AsTraitObject for SomeStruct {
+///     fn as_trait_object<'a>(&'a self, type_id: DynTypeId) -> Option<Dyn<'a>> {
+///         match_dyn_type_id!(type_id => {
+///             SomeTrait => self.index.get_or_init(|| Index(42)),
+///             SomeOtherTrait => self,
+///         })
+///     }
+/// }
+///
+/// let obj = SomeStruct { index: OnceCell::new() };
+/// assert!(obj.index.get().is_none());
+/// let index = cast_trait_object::<dyn SomeTrait>(&obj).unwrap();
+/// assert_eq!(index.some_trait_method(), 42);
+/// ```
+///
+/// You are welcome to hand-write similar code yourself if you prefer. This
+/// macro is just a convenience helper to help focus on just listing which
+/// traits are supported, rather than exposing the implementation details.
+#[doc(inline)]
+pub use __match_dyn_type_id as match_dyn_type_id;
+
+/// Helper for implementing lookups from [`DynTypeId`] to [`DynMut`] for
+/// a specified set of traits.
+///
+/// This is just like [`match_dyn_type_id`] except that it produces
+/// [`DynMut`] results instead of [`Dyn`], and must therefore be used
+/// with a mutable reference to something that implements each of the listed
+/// traits.
+///
+/// ```
+/// # use any_dyn::traitcast::{AsTraitObjectMut, match_dyn_type_id_mut};
+/// # use any_dyn::{DynMut, DynTypeId};
+/// # trait SomeTrait { /* ... */ }
+/// # trait SomeOtherTrait { /* ... */ }
+/// # struct SomeStruct { /* ... */ }
+/// # impl SomeTrait for SomeStruct { /* ... */ }
+/// # impl SomeOtherTrait for SomeStruct { /* ... */ }
+/// impl AsTraitObjectMut for SomeStruct {
+///     fn as_trait_object_mut<'a>(&'a mut self, type_id: DynTypeId) -> Option<DynMut<'a>> {
+///         // The macro expands to an expression that returns Option<DynMut>.
+///         match_dyn_type_id_mut!(self, type_id => SomeTrait, SomeOtherTrait)
 ///     }
 /// }
 /// ```
@@ -236,11 +905,919 @@ pub use __match_dyn_type_id as match_dyn_type_id;
 /// listed traits, all of which be `dyn`-compatible and `'static`. `type_id`
 /// must be a value of type `DynTypeId`.
 ///
+/// This macro also accepts the braced list of arms described for
+/// [`match_dyn_type_id`], with each expression producing a mutable reference.
+///
 /// Refer to [`match_dyn_type_id`] for a more complete example. As with that
 /// macro, you can write an equivalent `if`/`else if`/`else` sequence yourself
 /// directly if you prefer.
 #[doc(inline)]
 pub use __match_dyn_type_id_mut as match_dyn_type_id_mut;
 
-#[expect(unused)]
-type DynMutUsed<'a> = DynMut<'a>;
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __forward_as_trait_object {
+    ($ty:ty => self $(. $field:tt)+ : $($trait_n:path),+ $(,)?) => {
+        impl $crate::traitcast::AsTraitObject for $ty {
+            fn as_trait_object<'a>(
+                &'a self,
+                type_id: $crate::DynTypeId,
+            ) -> ::core::option::Option<$crate::Dyn<'a>> {
+                $crate::traitcast::match_dyn_type_id!(&self $(. $field)+, type_id => $($trait_n),+)
+            }
+        }
+    };
+}
+
+/// Implements [`AsTraitObject`] for a wrapper type by offering trait objects
+/// for one of its fields.
+///
+/// `forward_as_trait_object!(Wrapper => self.0 : SomeTrait)` is shorthand for
+/// an implementation of [`AsTraitObject::as_trait_object`] that uses
+/// [`match_dyn_type_id`] to offer `&self.0` as `dyn SomeTrait`. This suits
+/// the newtype pattern, where a wrapper type exposes the capabilities of the
+/// value it wraps without implementing each trait itself. Multiple traits
+/// can be listed, separated by commas.
+///
+/// ```
+/// use any_dyn::traitcast::{cast_trait_object, forward_as_trait_object};
+///
+/// trait SomeTrait {
+///     fn name(&self) -> &'static str;
+/// }
+///
+/// struct Inner;
+/// impl SomeTrait for Inner {
+///     fn name(&self) -> &'static str {
+///         "inner"
+///     }
+/// }
+///
+/// #[repr(transparent)]
+/// struct Wrapper(Inner);
+/// forward_as_trait_object!(Wrapper => self.0 : SomeTrait);
+///
+/// let obj = Wrapper(Inner);
+/// let inner = cast_trait_object::<dyn SomeTrait>(&obj).unwrap();
+/// assert_eq!(inner.name(), "inner");
+/// ```
+///
+/// The resulting trait objects refer to the field rather than to the
+/// wrapper, so the wrapper need not implement the listed traits itself, and
+/// need not be `#[repr(transparent)]`.
+#[doc(inline)]
+pub use __forward_as_trait_object as forward_as_trait_object;
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __erase_field {
+    ($root:ident $(. $field:tt)+ : $trait_n:path) => {
+        $crate::Dyn::new(&$root $(. $field)+ as &dyn $trait_n)
+    };
+}
+
+/// Helper for erasing a field of a struct as a trait object of a specified
+/// trait.
+///
+/// `erase_field!(obj.field : SomeTrait)` is shorthand for
+/// `Dyn::new(&obj.field as &dyn SomeTrait)`. It's intended for exposing the
+/// capabilities of a sub-object through its parent's implementation of
+/// [`AsTraitObject`], and works both for fields of concrete types and for
+/// unsized `dyn SomeTrait` fields.
+///
+/// ```
+/// use any_dyn::traitcast::{AsTraitObject, cast_trait_object, erase_field};
+/// use any_dyn::{Dyn, DynTypeId};
+///
+/// trait Engine {
+///     fn start(&self) -> &'static str;
+/// }
+///
+/// struct Diesel;
+/// impl Engine for Diesel {
+///     fn start(&self) -> &'static str {
+///         "vroom"
+///     }
+/// }
+///
+/// struct Car {
+///     engine: Diesel,
+/// }
+///
+/// impl AsTraitObject for Car {
+///     fn as_trait_object<'a>(&'a self, type_id: DynTypeId) -> Option<Dyn<'a>> {
+///         if type_id == DynTypeId::of::<dyn Engine>() {
+///             Some(erase_field!(self.engine : Engine))
+///         } else {
+///             None
+///         }
+///     }
+/// }
+///
+/// let car = Car { engine: Diesel };
+/// assert_eq!(cast_trait_object::<dyn Engine>(&car).unwrap().start(), "vroom");
+/// ```
+///
+/// The field path can include multiple levels, as in
+/// `erase_field!(self.inner.engine : Engine)`, and tuple fields, as in
+/// `erase_field!(self.0 : Engine)`.
+#[doc(inline)]
+pub use __erase_field as erase_field;
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __cast_through {
+    ($value:expr, $dyn_ty:ty, $($pat:pat => $handle:expr),+ $(,)?) => {
+        match $value {
+            $(
+            $pat => $crate::traitcast::cast_trait_object::<$dyn_ty>($handle),
+            )+
+            #[allow(unreachable_patterns)]
+            _ => ::core::option::Option::None,
+        }
+    };
+}
+
+/// Helper for casting an [`AsTraitObject`] implementer that is wrapped in a
+/// user-defined type, such as an enum variant.
+///
+/// `cast_through!(value, dyn SomeTrait, Pattern(x) => x)` matches `value`
+/// against each of the given patterns in turn, and for the first that matches
+/// it passes the handle expression to [`cast_trait_object`]. The result is
+/// [`None`] if none of the patterns match, or if the cast fails.
+///
+/// ```
+/// use any_dyn::traitcast::{AsTraitObject, cast_through, match_dyn_type_id};
+/// use any_dyn::{Dyn, DynTypeId};
+///
+/// trait Greet {
+///     fn greet(&self) -> &'static str;
+/// }
+///
+/// struct Greeter;
+/// impl Greet for Greeter {
+///     fn greet(&self) -> &'static str {
+///         "hello"
+///     }
+/// }
+/// impl AsTraitObject for Greeter {
+///     fn as_trait_object<'a>(&'a self, type_id: DynTypeId) -> Option<Dyn<'a>> {
+///         match_dyn_type_id!(self, type_id => Greet)
+///     }
+/// }
+///
+/// enum Slot {
+///     Owned(Box<dyn AsTraitObject>),
+///     Borrowed(&'static dyn AsTraitObject),
+///     Empty,
+/// }
+///
+/// fn greet(slot: &Slot) -> Option<&'static str> {
+///     let greeter = cast_through!(
+///         slot,
+///         dyn Greet,
+///         Slot::Owned(obj) => &**obj,
+///         Slot::Borrowed(obj) => *obj,
+///     )?;
+///     Some(greeter.greet())
+/// }
+///
+/// assert_eq!(greet(&Slot::Owned(Box::new(Greeter))), Some("hello"));
+/// assert_eq!(greet(&Slot::Borrowed(&Greeter)), Some("hello"));
+/// assert_eq!(greet(&Slot::Empty), None);
+/// ```
+///
+/// Each handle expression must be a reference to something that implements
+/// [`AsTraitObject`]. With the `alloc` feature enabled that includes
+/// references to the smart pointers that [`AsTraitObject`] is implemented
+/// for, so the `Slot::Owned` arm above could use `obj` directly.
+#[doc(inline)]
+pub use __cast_through as cast_through;
+
+/// An iterator over the trait objects offered by an [`AsTraitObject`]
+/// implementer, returned by [`AsTraitObject::trait_objects`].
+///
+/// Each item is a trait object along with the [`DynTypeId`] of its trait
+/// object type.
+#[derive(Clone)]
+pub struct TraitObjects<'a> {
+    obj: Option<&'a dyn AsTraitObject>,
+    type_ids: core::slice::Iter<'static, DynTypeId>,
+}
+
+impl<'a> TraitObjects<'a> {
+    /// Returns an iterator over the trait objects `obj` offers for each of
+    /// the given trait object types, skipping any it does not offer.
+    ///
+    /// [`trait_objects`] is a more convenient way to call this.
+    #[inline]
+    pub fn new(obj: &'a dyn AsTraitObject, type_ids: &'static [DynTypeId]) -> Self {
+        Self {
+            obj: Some(obj),
+            type_ids: type_ids.iter(),
+        }
+    }
+
+    /// Returns an iterator that produces no items.
+    #[inline]
+    pub fn empty() -> Self {
+        Self {
+            obj: None,
+            type_ids: [].iter(),
+        }
+    }
+}
+
+impl<'a> Iterator for TraitObjects<'a> {
+    type Item = (DynTypeId, Dyn<'a>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let obj = self.obj?;
+        self.type_ids
+            .by_ref()
+            .find_map(|&type_id| Some((type_id, obj.as_trait_object(type_id)?)))
+    }
+}
+
+impl core::fmt::Debug for TraitObjects<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("TraitObjects")
+            .field("type_ids", &self.type_ids.as_slice())
+            .finish_non_exhaustive()
+    }
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __trait_objects {
+    ($self:expr => $($trait_n:path),+ ) => {{
+        $crate::traitcast::TraitObjects::new(
+            $self,
+            const { &[$($crate::DynTypeId::of::<dyn $trait_n>()),+] },
+        )
+    }};
+}
+
+/// Helper for implementing [`AsTraitObject::trait_objects`] for a specified
+/// set of traits.
+///
+/// This takes the same arguments as [`match_dyn_type_id`], except for the
+/// type id, so both methods can be implemented from the same list of traits.
+/// Each trait object is obtained by calling
+/// [`AsTraitObject::as_trait_object`], so the two methods cannot disagree.
+///
+/// ```
+/// use any_dyn::traitcast::{AsTraitObject, TraitObjects, match_dyn_type_id, trait_objects};
+/// use any_dyn::{Dyn, DynTypeId};
+///
+/// trait SomeTrait { /* ... */ }
+/// trait SomeOtherTrait { /* ... */ }
+///
+/// struct SomeStruct { /* ... */ }
+/// impl SomeTrait for SomeStruct { /* ... */ }
+/// impl SomeOtherTrait for SomeStruct { /* ... */ }
+///
+/// impl AsTraitObject for SomeStruct {
+///     fn as_trait_object<'a>(&'a self, type_id: DynTypeId) -> Option<Dyn<'a>> {
+///         match_dyn_type_id!(self, type_id => SomeTrait, SomeOtherTrait)
+///     }
+///
+///     fn trait_objects<'a>(&'a self) -> TraitObjects<'a> {
+///         trait_objects!(self => SomeTrait, SomeOtherTrait)
+///     }
+/// }
+///
+/// let obj = SomeStruct {};
+/// let type_ids: Vec<DynTypeId> = obj.trait_objects().map(|(id, _)| id).collect();
+/// assert_eq!(
+///     type_ids,
+///     [DynTypeId::of::<dyn SomeTrait>(), DynTypeId::of::<dyn SomeOtherTrait>()],
+/// );
+/// ```
+#[doc(inline)]
+pub use __trait_objects as trait_objects;
+
+/// Sorts a dispatch table by its [`DynTypeId`] keys, so that it can be
+/// searched using [`binary_search_dispatch`].
+///
+/// This is for dispatch tables assembled as data at runtime, where the
+/// number of entries is large enough that a linear search over them would
+/// be too slow. For sets of traits known at compile time, prefer
+/// [`match_dyn_type_id`].
+///
+/// The order of [`DynTypeId`] values is arbitrary, and is consistent only
+/// within a single run of a single program. A table sorted by this function
+/// must therefore not be persisted or sent to another process, and must be
+/// sorted again each time the program starts.
+///
+/// If the table has more than one entry with the same key then their
+/// relative order is unspecified, and so is which of them
+/// [`binary_search_dispatch`] will find.
+///
+/// ```
+/// # use any_dyn::{DynTypeId, traitcast::{binary_search_dispatch, sort_dispatch_table}};
+/// # trait SomeTrait {}
+/// # trait SomeOtherTrait {}
+/// # trait UnsupportedTrait {}
+/// let mut table = [
+///     (DynTypeId::of::<dyn SomeTrait>(), "some"),
+///     (DynTypeId::of::<dyn SomeOtherTrait>(), "other"),
+/// ];
+/// sort_dispatch_table(&mut table);
+///
+/// let found = binary_search_dispatch(&table, DynTypeId::of::<dyn SomeOtherTrait>());
+/// assert_eq!(found, Some(&"other"));
+/// let found = binary_search_dispatch(&table, DynTypeId::of::<dyn UnsupportedTrait>());
+/// assert_eq!(found, None);
+/// ```
+#[inline]
+pub fn sort_dispatch_table<T>(entries: &mut [(DynTypeId, T)]) {
+    entries.sort_unstable_by_key(|&(type_id, _)| type_id);
+}
+
+/// Finds the value for `type_id` in a dispatch table that was previously
+/// sorted using [`sort_dispatch_table`], in logarithmic time.
+///
+/// If the table is not sorted then the result is unspecified, but memory
+/// safe. Refer to [`sort_dispatch_table`] for more information and an
+/// example.
+#[inline]
+pub fn binary_search_dispatch<T>(entries: &[(DynTypeId, T)], type_id: DynTypeId) -> Option<&T> {
+    let pos = entries
+        .binary_search_by_key(&type_id, |&(type_id, _)| type_id)
+        .ok()?;
+    Some(&entries[pos].1)
+}
+
+/// Assigns compact integer tokens to [`DynTypeId`] values.
+///
+/// Each distinct [`DynTypeId`] passed to [`TypeTokenMap::intern`] is assigned
+/// the next token in sequence, starting at zero, and interning the same
+/// [`DynTypeId`] again returns the same token. [`TypeTokenMap::resolve`]
+/// then maps a token back to its [`DynTypeId`].
+///
+/// Tokens are meaningful only for the same [`TypeTokenMap`] they were obtained
+/// from, and only during the current run of the program. Don't save them
+/// anywhere that might be read by a different process.
+///
+/// ```
+/// # use any_dyn::{DynTypeId, traitcast::TypeTokenMap};
+/// # trait SomeTrait {}
+/// # trait SomeOtherTrait {}
+/// let mut tokens = TypeTokenMap::new();
+/// let some = tokens.intern(DynTypeId::of::<dyn SomeTrait>());
+/// let other = tokens.intern(DynTypeId::of::<dyn SomeOtherTrait>());
+/// assert_eq!(some, 0);
+/// assert_eq!(other, 1);
+/// assert_eq!(tokens.intern(DynTypeId::of::<dyn SomeTrait>()), some);
+/// assert_eq!(tokens.resolve(other), Some(DynTypeId::of::<dyn SomeOtherTrait>()));
+/// assert_eq!(tokens.resolve(2), None);
+/// ```
+///
+/// This is available only when the `alloc` feature is enabled.
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone, Default)]
+pub struct TypeTokenMap {
+    tokens: alloc::collections::BTreeMap<DynTypeId, u64>,
+    ids: alloc::vec::Vec<DynTypeId>,
+}
+
+#[cfg(feature = "alloc")]
+impl TypeTokenMap {
+    /// Creates an empty [`TypeTokenMap`].
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            tokens: alloc::collections::BTreeMap::new(),
+            ids: alloc::vec::Vec::new(),
+        }
+    }
+
+    /// Returns the token for `id`, assigning a new one if `id` hasn't been
+    /// interned in this map before.
+    pub fn intern(&mut self, id: DynTypeId) -> u64 {
+        *self.tokens.entry(id).or_insert_with(|| {
+            let token = self.ids.len() as u64;
+            self.ids.push(id);
+            token
+        })
+    }
+
+    /// Returns the [`DynTypeId`] that was assigned `token` by an earlier call
+    /// to [`TypeTokenMap::intern`] on this same map, if any.
+    #[inline]
+    pub fn resolve(&self, token: u64) -> Option<DynTypeId> {
+        let idx = usize::try_from(token).ok()?;
+        self.ids.get(idx).copied()
+    }
+}
+
+/// A table of type-erased trait objects, keyed by their trait object types.
+///
+/// This is a data-driven alternative to [`match_dyn_type_id`]: each supported
+/// trait is represented by an entry in the table rather than by a branch in
+/// the code. [`TraitTable`] itself implements [`AsTraitObject`] by looking up
+/// the requested trait object type in the table.
+///
+/// The main advantage of [`TraitTable`] is that the set of supported traits
+/// can be decided at runtime. It is not a performance optimization: the chain
+/// of comparisons generated by [`match_dyn_type_id`] compares against values
+/// known at compile time, and in the benchmarks in `benches/dispatch.rs` it
+/// remains faster than a [`TraitTable`] lookup even with over a hundred
+/// supported traits.
+///
+/// ```
+/// # use any_dyn::{Dyn, DynTypeId, traitcast::{TraitTable, cast_trait_object}};
+/// # trait SomeTrait { fn some_trait_method(&self) {} }
+/// # trait SomeOtherTrait {}
+/// # struct SomeStruct {}
+/// # impl SomeTrait for SomeStruct {}
+/// let obj = SomeStruct {};
+/// let mut table = TraitTable::new();
+/// table.insert(Dyn::new(&obj as &dyn SomeTrait));
+///
+/// assert!(table.get(DynTypeId::of::<dyn SomeOtherTrait>()).is_none());
+/// table[DynTypeId::of::<dyn SomeTrait>()]
+///     .expect_cast::<dyn SomeTrait>()
+///     .some_trait_method();
+/// if let Some(trait_obj) = cast_trait_object::<dyn SomeTrait>(&table) {
+///     trait_obj.some_trait_method();
+/// }
+/// ```
+///
+/// This is available only when the `alloc` feature is enabled.
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone, Default)]
+pub struct TraitTable<'a> {
+    entries: alloc::collections::BTreeMap<DynTypeId, Dyn<'a>>,
+}
+
+#[cfg(feature = "alloc")]
+impl<'a> TraitTable<'a> {
+    /// Creates an empty [`TraitTable`].
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            entries: alloc::collections::BTreeMap::new(),
+        }
+    }
+
+    /// Adds the given trait object to the table, keyed by its trait object
+    /// type, returning the entry it replaced if there was already one for
+    /// the same trait object type.
+    #[inline]
+    pub fn insert(&mut self, obj: Dyn<'a>) -> Option<Dyn<'a>> {
+        self.entries.insert(obj.dyn_type_id(), obj)
+    }
+
+    /// Removes and returns the entry for the given trait object type, if any.
+    #[inline]
+    pub fn remove(&mut self, type_id: DynTypeId) -> Option<Dyn<'a>> {
+        self.entries.remove(&type_id)
+    }
+
+    /// Returns the entry for the given trait object type, if any.
+    #[inline]
+    pub fn get(&self, type_id: DynTypeId) -> Option<Dyn<'a>> {
+        self.entries.get(&type_id).copied()
+    }
+
+    /// Returns the number of entries in the table.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if the table has no entries.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<'a> core::ops::Index<DynTypeId> for TraitTable<'a> {
+    type Output = Dyn<'a>;
+
+    /// Returns the entry for the given trait object type.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no entry for the given trait object type. Use
+    /// [`TraitTable::get`] to handle that case without panicking.
+    #[track_caller]
+    fn index(&self, type_id: DynTypeId) -> &Dyn<'a> {
+        match self.entries.get(&type_id) {
+            Some(obj) => obj,
+            #[cfg(feature = "minimal-panic")]
+            None => panic!("TraitTable has no entry for requested trait object type"),
+            #[cfg(not(feature = "minimal-panic"))]
+            None => panic!("TraitTable has no entry for {type_id:?}"),
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl AsTraitObject for TraitTable<'_> {
+    #[inline]
+    fn as_trait_object<'a>(&'a self, type_id: DynTypeId) -> Option<Dyn<'a>> {
+        self.get(type_id)
+    }
+}
+
+/// A registry of named, type-erased operations, each of which is a trait
+/// object of some function trait such as `dyn Fn(u32) -> u32`.
+///
+/// This is intended for plugin interfaces and similar situations where the
+/// set of available operations, and their signatures, are decided at
+/// runtime. Each entry remembers its own trait object type, so looking up
+/// an operation with the wrong signature fails rather than misbehaving.
+///
+/// [`OpTable::invoke`] handles the common case of operations implementing
+/// [`Fn`] with a single argument; operations that need several arguments can
+/// take a tuple. [`OpTable::get_as`] can retrieve operations of any other
+/// trait object type.
+///
+/// ```
+/// # use any_dyn::{Dyn, traitcast::OpTable};
+/// let double = |v: u32| v * 2;
+/// let greet = |name: &'static str| format!("hello, {name}");
+///
+/// let mut ops = OpTable::new();
+/// ops.insert("double", Dyn::new(&double as &dyn Fn(u32) -> u32));
+/// ops.insert("greet", Dyn::new(&greet as &dyn Fn(&'static str) -> String));
+///
+/// assert_eq!(ops.invoke::<u32, u32>("double", 21), Some(42));
+/// assert_eq!(
+///     ops.invoke::<&str, String>("greet", "plugin").as_deref(),
+///     Some("hello, plugin"),
+/// );
+/// // Operations can't be called with the wrong signature...
+/// assert_eq!(ops.invoke::<u64, u64>("double", 21), None);
+/// // ...and operations that aren't registered can't be called at all.
+/// assert_eq!(ops.invoke::<u32, u32>("triple", 21), None);
+/// ```
+///
+/// This is available only when the `alloc` feature is enabled.
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone, Default)]
+pub struct OpTable<'a> {
+    entries: alloc::collections::BTreeMap<alloc::string::String, Dyn<'a>>,
+}
+
+#[cfg(feature = "alloc")]
+impl<'a> OpTable<'a> {
+    /// Creates an empty [`OpTable`].
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            entries: alloc::collections::BTreeMap::new(),
+        }
+    }
+
+    /// Registers `op` under the given name, returning the operation it
+    /// replaced if there was already one with the same name.
+    #[inline]
+    pub fn insert(
+        &mut self,
+        name: impl Into<alloc::string::String>,
+        op: Dyn<'a>,
+    ) -> Option<Dyn<'a>> {
+        self.entries.insert(name.into(), op)
+    }
+
+    /// Removes and returns the operation with the given name, if any.
+    #[inline]
+    pub fn remove(&mut self, name: &str) -> Option<Dyn<'a>> {
+        self.entries.remove(name)
+    }
+
+    /// Returns the operation with the given name, if any.
+    #[inline]
+    pub fn get(&self, name: &str) -> Option<Dyn<'a>> {
+        self.entries.get(name).copied()
+    }
+
+    /// Returns the operation with the given name as a `Dyn` trait object,
+    /// or `None` if there is no such operation or it was registered with a
+    /// different trait object type.
+    #[inline]
+    pub fn get_as<Dyn: TraitObject + ?Sized + 'static>(&self, name: &str) -> Option<&'a Dyn> {
+        self.get(name)?.cast::<Dyn>()
+    }
+
+    /// Calls the operation with the given name, which must have been
+    /// registered as a `dyn Fn(A) -> R`, and returns its result.
+    ///
+    /// Returns `None` if there is no such operation or it was registered with
+    /// a different trait object type, in which case the operation is not
+    /// called.
+    #[inline]
+    pub fn invoke<A: 'static, R: 'static>(&self, name: &str, arg: A) -> Option<R> {
+        let op = self.get_as::<dyn Fn(A) -> R>(name)?;
+        Some(op(arg))
+    }
+
+    /// Returns an iterator over the names of the registered operations, in
+    /// lexicographical order.
+    #[inline]
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.entries.keys().map(alloc::string::String::as_str)
+    }
+
+    /// Returns the number of registered operations.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if there are no registered operations.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+/// A table of handlers for erased trait objects, each of which knows the
+/// specific trait object type it handles.
+///
+/// This connects data-driven [`DynTypeId`] values to statically-typed code:
+/// each handler is registered for a particular trait object type using
+/// [`DynDispatcher::register`], and [`DynDispatcher::dispatch`] then calls
+/// whichever handler matches the trait object type of a given [`Dyn`]. This
+/// is similar to an "open method" whose implementations are chosen by the
+/// trait an object was erased as, rather than by its concrete type.
+///
+/// ```
+/// # use any_dyn::{Dyn, traitcast::DynDispatcher};
+/// # use core::fmt::{Debug, Display};
+/// let mut describe = DynDispatcher::new();
+/// describe.register::<dyn Display>(|v| format!("displayed as {v}"));
+/// describe.register::<dyn Debug>(|v| format!("debugged as {v:?}"));
+///
+/// let (a, b) = (1_u8, "two");
+/// assert_eq!(
+///     describe.dispatch(Dyn::new(&a as &dyn Display)).as_deref(),
+///     Some("displayed as 1"),
+/// );
+/// assert_eq!(
+///     describe.dispatch(Dyn::new(&b as &dyn Debug)).as_deref(),
+///     Some("debugged as \"two\""),
+/// );
+/// assert_eq!(describe.dispatch(Dyn::new(&a as &dyn core::any::Any)), None);
+/// ```
+///
+/// This is available only when the `alloc` feature is enabled.
+#[cfg(feature = "alloc")]
+pub struct DynDispatcher<R = ()> {
+    handlers: alloc::collections::BTreeMap<DynTypeId, DynHandler<R>>,
+}
+
+#[cfg(feature = "alloc")]
+type DynHandler<R> = alloc::boxed::Box<dyn Fn(Dyn<'_>) -> R>;
+
+#[cfg(feature = "alloc")]
+impl<R> DynDispatcher<R> {
+    /// Creates a [`DynDispatcher`] with no handlers.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            handlers: alloc::collections::BTreeMap::new(),
+        }
+    }
+
+    /// Registers `handler` to be called for trait objects of type `Dyn`,
+    /// replacing any handler previously registered for the same type.
+    pub fn register<Dyn: TraitObject + ?Sized + 'static>(
+        &mut self,
+        handler: impl Fn(&Dyn) -> R + 'static,
+    ) {
+        self.handlers.insert(
+            DynTypeId::of::<Dyn>(),
+            alloc::boxed::Box::new(move |obj| {
+                // Handlers are only ever called for objects whose trait object
+                // type matches the key they were registered under.
+                handler(obj.expect_cast::<Dyn>())
+            }),
+        );
+    }
+
+    /// Removes the handler for the given trait object type, returning `true`
+    /// if there was one.
+    #[inline]
+    pub fn unregister(&mut self, type_id: DynTypeId) -> bool {
+        self.handlers.remove(&type_id).is_some()
+    }
+
+    /// Returns `true` if there is a handler for the given trait object type.
+    #[inline]
+    pub fn handles(&self, type_id: DynTypeId) -> bool {
+        self.handlers.contains_key(&type_id)
+    }
+
+    /// Calls the handler registered for the trait object type of `obj`, and
+    /// returns its result, or returns `None` if there is no such handler.
+    #[inline]
+    pub fn dispatch(&self, obj: Dyn<'_>) -> Option<R> {
+        let handler = self.handlers.get(&obj.dyn_type_id())?;
+        Some(handler(obj))
+    }
+
+    /// Returns the number of registered handlers.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.handlers.len()
+    }
+
+    /// Returns `true` if there are no registered handlers.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.handlers.is_empty()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<R> Default for DynDispatcher<R> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<R> core::fmt::Debug for DynDispatcher<R> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_set().entries(self.handlers.keys()).finish()
+    }
+}
+
+/// An [`AsTraitObject`] implementation that searches an ordered list of other
+/// [`AsTraitObject`] implementers, returning the first trait object offered.
+///
+/// This allows composing several sources of trait objects into one, such as
+/// a handle whose capabilities are provided by a few separate components.
+/// The list is borrowed, so it can be an array, a slice of a `Vec`, or any
+/// other contiguous storage.
+///
+/// ```
+/// # use any_dyn::traitcast::{AsTraitObject, HandleChain, cast_trait_object};
+/// # use any_dyn::{Dyn, DynTypeId, traitcast::match_dyn_type_id};
+/// # trait Draw { fn draw(&self) {} }
+/// # trait Update { fn update(&self) {} }
+/// # struct Renderer;
+/// # struct Physics;
+/// # impl Draw for Renderer {}
+/// # impl Update for Physics {}
+/// # impl AsTraitObject for Renderer {
+/// #     fn as_trait_object<'a>(&'a self, type_id: DynTypeId) -> Option<Dyn<'a>> {
+/// #         match_dyn_type_id!(self, type_id => Draw)
+/// #     }
+/// # }
+/// # impl AsTraitObject for Physics {
+/// #     fn as_trait_object<'a>(&'a self, type_id: DynTypeId) -> Option<Dyn<'a>> {
+/// #         match_dyn_type_id!(self, type_id => Update)
+/// #     }
+/// # }
+/// let sources: [&dyn AsTraitObject; 2] = [&Renderer, &Physics];
+/// let chain = HandleChain::new(&sources);
+/// cast_trait_object::<dyn Draw>(&chain).unwrap().draw();
+/// cast_trait_object::<dyn Update>(&chain).unwrap().update();
+/// ```
+#[derive(Clone, Copy)]
+pub struct HandleChain<'a> {
+    sources: &'a [&'a dyn AsTraitObject],
+}
+
+impl<'a> HandleChain<'a> {
+    /// Creates a [`HandleChain`] that searches the given sources in order.
+    #[inline]
+    pub const fn new(sources: &'a [&'a dyn AsTraitObject]) -> Self {
+        Self { sources }
+    }
+
+    /// Returns the sources this chain searches, in order.
+    #[inline]
+    pub const fn sources(&self) -> &'a [&'a dyn AsTraitObject] {
+        self.sources
+    }
+}
+
+impl AsTraitObject for HandleChain<'_> {
+    fn as_trait_object<'a>(&'a self, type_id: DynTypeId) -> Option<Dyn<'a>> {
+        self.sources
+            .iter()
+            .find_map(|source| source.as_trait_object(type_id))
+    }
+}
+
+impl core::fmt::Debug for HandleChain<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("HandleChain")
+            .field("len", &self.sources.len())
+            .finish_non_exhaustive()
+    }
+}
+
+/// A fixed-capacity table of type-erased trait objects, keyed by their trait
+/// object types.
+///
+/// This is like `TraitTable`, but stores up to `N` entries inline without
+/// any heap allocation, and so is available even without the `alloc`
+/// feature. Lookups scan the entries linearly.
+///
+/// ```
+/// # use any_dyn::{Dyn, DynTypeId, traitcast::{FixedTraitTable, cast_trait_object}};
+/// # trait SomeTrait { fn some_trait_method(&self) {} }
+/// # trait SomeOtherTrait {}
+/// # struct SomeStruct {}
+/// # impl SomeTrait for SomeStruct {}
+/// # impl SomeOtherTrait for SomeStruct {}
+/// let obj = SomeStruct {};
+/// let mut table = FixedTraitTable::<1>::new();
+/// table.insert(Dyn::new(&obj as &dyn SomeTrait)).unwrap();
+/// assert!(table.insert(Dyn::new(&obj as &dyn SomeOtherTrait)).is_err());
+///
+/// if let Some(trait_obj) = cast_trait_object::<dyn SomeTrait>(&table) {
+///     trait_obj.some_trait_method();
+/// }
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct FixedTraitTable<'a, const N: usize> {
+    entries: [Option<Dyn<'a>>; N],
+}
+
+impl<'a, const N: usize> FixedTraitTable<'a, N> {
+    /// Creates an empty [`FixedTraitTable`].
+    #[inline]
+    pub const fn new() -> Self {
+        Self { entries: [None; N] }
+    }
+
+    /// Adds the given trait object to the table, keyed by its trait object
+    /// type.
+    ///
+    /// Returns the entry it replaced if there was already one for the same
+    /// trait object type. If there is no existing entry and the table is
+    /// already full, returns `Err` with the given trait object instead.
+    pub fn insert(&mut self, obj: Dyn<'a>) -> Result<Option<Dyn<'a>>, Dyn<'a>> {
+        let type_id = obj.dyn_type_id();
+        if let Some(existing) = self
+            .entries
+            .iter_mut()
+            .flatten()
+            .find(|entry| entry.dyn_type_id() == type_id)
+        {
+            return Ok(Some(core::mem::replace(existing, obj)));
+        }
+        match self.entries.iter_mut().find(|entry| entry.is_none()) {
+            Some(slot) => {
+                *slot = Some(obj);
+                Ok(None)
+            }
+            None => Err(obj),
+        }
+    }
+
+    /// Removes and returns the entry for the given trait object type, if any.
+    pub fn remove(&mut self, type_id: DynTypeId) -> Option<Dyn<'a>> {
+        self.entries
+            .iter_mut()
+            .find(|entry| entry.is_some_and(|obj| obj.dyn_type_id() == type_id))?
+            .take()
+    }
+
+    /// Returns the entry for the given trait object type, if any.
+    pub fn get(&self, type_id: DynTypeId) -> Option<Dyn<'a>> {
+        self.entries
+            .iter()
+            .flatten()
+            .find(|obj| obj.dyn_type_id() == type_id)
+            .copied()
+    }
+
+    /// Returns the number of entries in the table.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.entries.iter().flatten().count()
+    }
+
+    /// Returns `true` if the table has no entries.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.entries.iter().all(Option::is_none)
+    }
+}
+
+impl<const N: usize> Default for FixedTraitTable<'_, N> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> AsTraitObject for FixedTraitTable<'_, N> {
+    #[inline]
+    fn as_trait_object<'a>(&'a self, type_id: DynTypeId) -> Option<Dyn<'a>> {
+        self.get(type_id)
+    }
+}
@@ -0,0 +1,49 @@
+use core::any::Any;
+
+use downcast_rs::Downcast;
+
+use crate::{Dyn, DynTypeId};
+
+/// Returns a `dyn Any` trait object for the given [`Downcast`] implementer if
+/// `type_id` identifies `dyn Any`, or [`None`] otherwise.
+///
+/// This is intended for use in implementations of
+/// [`AsTraitObject`](super::AsTraitObject) for types that already use the
+/// [`downcast-rs`](https://docs.rs/downcast-rs) crate's pattern, including
+/// trait objects for traits that have [`Downcast`] as a supertrait. It can be
+/// used as the fallback after checking for other trait object types:
+///
+/// ```
+/// # use any_dyn::{
+/// #     Dyn,
+/// #     DynTypeId,
+/// #     traitcast::{AsTraitObject, as_trait_object_via_downcast, match_dyn_type_id},
+/// # };
+/// # use downcast_rs::Downcast;
+/// # trait WithName { fn name(&self) -> &str; }
+/// # struct Named(String);
+/// # impl WithName for Named { fn name(&self) -> &str { &self.0 } }
+/// impl AsTraitObject for Named {
+///     fn as_trait_object<'a>(&'a self, type_id: DynTypeId) -> Option<Dyn<'a>> {
+///         match_dyn_type_id!(self, type_id => WithName)
+///             .or_else(|| as_trait_object_via_downcast(self, type_id))
+///     }
+/// }
+/// ```
+///
+/// In the other direction, an [`AsTraitObject`](super::AsTraitObject)
+/// implementer that offers `dyn Any`, such as one using this function, can
+/// be downcast to its concrete type using
+/// [`cast_then_downcast`](super::cast_then_downcast).
+///
+/// This is available only when the `downcast-rs` feature is enabled.
+#[inline]
+pub fn as_trait_object_via_downcast<T: Downcast + ?Sized>(
+    obj: &T,
+    type_id: DynTypeId,
+) -> Option<Dyn<'_>> {
+    if type_id != DynTypeId::of::<dyn Any>() {
+        return None;
+    }
+    Some(Dyn::new(obj.as_any()))
+}
@@ -0,0 +1,141 @@
+use alloc::{collections::BTreeMap, sync::Arc};
+use core::any::{Any, TypeId};
+use std::sync::{PoisonError, RwLock};
+
+use crate::{Dyn, DynTypeId, TraitObject};
+
+type Caster = Arc<dyn for<'a> Fn(&'a dyn Any) -> Option<Dyn<'a>> + Send + Sync>;
+
+/// A registry of trait object casts for types that cannot implement
+/// [`AsTraitObject`](super::AsTraitObject) themselves, such as types from
+/// other crates.
+///
+/// Each registration describes how to obtain a trait object of one
+/// particular trait object type from a value of one particular concrete type.
+/// [`ExternalCaster::cast`] can then cast any `&dyn Any` whose concrete type
+/// has a registration for the requested trait object type.
+///
+/// Most callers will use the global registry through [`register_external`]
+/// and [`cast_external`], but separate registries can be used for
+/// independent subsystems.
+///
+/// # Thread safety and initialization order
+///
+/// The registry is protected by a read/write lock, so it can be shared
+/// between threads and registrations can be added at any time. However,
+/// registrations are not automatic: a cast succeeds only if the corresponding
+/// registration was added before the cast was attempted, so programs should
+/// typically add all of their registrations during startup, before any
+/// casts are attempted.
+///
+/// This is available only when the `std` feature is enabled.
+#[derive(Default)]
+pub struct ExternalCaster {
+    casters: RwLock<BTreeMap<(TypeId, DynTypeId), Caster>>,
+}
+
+impl ExternalCaster {
+    /// Creates an empty registry.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            casters: RwLock::new(BTreeMap::new()),
+        }
+    }
+
+    /// Registers `f` as the way to obtain a trait object of type `Dyn` from a
+    /// value of type `T`, replacing any existing registration for the same
+    /// pair of types.
+    pub fn register<T, Dyn, F>(&self, f: F)
+    where
+        T: Any,
+        Dyn: TraitObject + ?Sized + 'static,
+        F: for<'a> Fn(&'a T) -> &'a Dyn + Send + Sync + 'static,
+    {
+        let caster: Caster = Arc::new(move |obj| {
+            let concrete = obj.downcast_ref::<T>()?;
+            Some(crate::Dyn::new(f(concrete)))
+        });
+        self.casters
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert((TypeId::of::<T>(), DynTypeId::of::<Dyn>()), caster);
+    }
+
+    /// Returns a trait object of type `Dyn` for `obj` if and only if there is
+    /// a registration for that trait object type and the concrete type of
+    /// `obj`.
+    pub fn cast<'a, Dyn: TraitObject + ?Sized + 'static>(
+        &self,
+        obj: &'a dyn Any,
+    ) -> Option<&'a Dyn> {
+        // The lock is released before calling the caster, so that a caster
+        // can itself use this registry, including to add registrations.
+        let caster = Arc::clone(
+            self.casters
+                .read()
+                .unwrap_or_else(PoisonError::into_inner)
+                .get(&(obj.type_id(), DynTypeId::of::<Dyn>()))?,
+        );
+        caster(obj)?.cast::<Dyn>()
+    }
+}
+
+impl core::fmt::Debug for ExternalCaster {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let casters = self.casters.read().unwrap_or_else(PoisonError::into_inner);
+        f.debug_struct("ExternalCaster")
+            .field("registrations", &casters.len())
+            .finish()
+    }
+}
+
+static GLOBAL: ExternalCaster = ExternalCaster::new();
+
+/// Registers `f` in the global [`ExternalCaster`] as the way to obtain a trait
+/// object of type `Dyn` from a value of type `T`.
+///
+/// Refer to [`ExternalCaster`] for information about thread safety and
+/// initialization order.
+///
+/// ```
+/// # use any_dyn::traitcast::{cast_external, register_external};
+/// # use std::any::Any;
+/// // Imagine that this trait belongs to the current crate, but that we want
+/// // to use it with a type from another crate that we cannot modify.
+/// trait Describe {
+///     fn describe(&self) -> String;
+/// }
+///
+/// impl Describe for std::time::Duration {
+///     fn describe(&self) -> String {
+///         format!("{} seconds", self.as_secs())
+///     }
+/// }
+///
+/// register_external::<std::time::Duration, dyn Describe, _>(|d| d);
+///
+/// let value: Box<dyn Any> = Box::new(std::time::Duration::from_secs(5));
+/// let describe = cast_external::<dyn Describe>(&*value).unwrap();
+/// assert_eq!(describe.describe(), "5 seconds");
+/// ```
+///
+/// This is available only when the `std` feature is enabled.
+#[inline]
+pub fn register_external<T, Dyn, F>(f: F)
+where
+    T: Any,
+    Dyn: TraitObject + ?Sized + 'static,
+    F: for<'a> Fn(&'a T) -> &'a Dyn + Send + Sync + 'static,
+{
+    GLOBAL.register::<T, Dyn, F>(f);
+}
+
+/// Casts `obj` to the trait object type `Dyn` using the registrations in the
+/// global [`ExternalCaster`].
+///
+/// This is available only when the `std` feature is enabled.
+#[inline]
+pub fn cast_external<Dyn: TraitObject + ?Sized + 'static>(obj: &dyn Any) -> Option<&Dyn> {
+    GLOBAL.cast::<Dyn>(obj)
+}
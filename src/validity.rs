@@ -0,0 +1,141 @@
+#[cfg(debug_assertions)]
+use core::ptr::NonNull;
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+/// A counter that a container of objects can advance whenever the objects
+/// it contains might have moved or been dropped, so that [`DynPtr`] values
+/// derived from those objects can be detected as stale.
+///
+/// Use [`DynPtr::with_generation`] to associate a [`DynPtr`] with the
+/// current generation of a [`Generation`]. If the generation has been
+/// advanced by the time the pointer is cast, the cast panics when debug
+/// assertions are enabled.
+///
+/// A [`Generation`] usually belongs to the container whose objects it
+/// tracks. [`Dyn::with_generation`] and [`DynMut::with_generation`] accept
+/// a generation borrowed for as long as the handle itself, so they suit
+/// handles that the container lends out. [`DynPtr`] has no lifetime, so
+/// [`DynPtr::with_generation`] requires a generation that lives for the
+/// rest of the program, such as one in a `static` item.
+///
+/// This is only a debugging aid, not a safety guarantee: it can only catch
+/// mistakes in code that remembers to call [`Generation::advance`] at the
+/// appropriate times.
+///
+/// ```
+/// # use any_dyn::{DynPtr, Generation};
+/// # use core::ptr::NonNull;
+/// # use std::panic::{AssertUnwindSafe, catch_unwind};
+/// # trait SomeTrait {}
+/// # struct SomeStruct;
+/// # impl SomeTrait for SomeStruct {}
+/// static GENERATION: Generation = Generation::new();
+///
+/// let obj = SomeStruct;
+/// let ptr = DynPtr::new(NonNull::from(&obj as &dyn SomeTrait)).with_generation(&GENERATION);
+/// assert!(ptr.cast::<dyn SomeTrait>().is_some());
+///
+/// // Imagine that the container which owns `obj` has just reallocated its
+/// // storage, and so it advances its generation...
+/// GENERATION.advance();
+/// # let _ = catch_unwind(AssertUnwindSafe(|| {
+/// ptr.cast::<dyn SomeTrait>(); // panics when debug assertions are enabled
+/// # }));
+/// ```
+///
+/// This is available only when the `debug-validity` feature is enabled.
+///
+/// [`Dyn::with_generation`]: crate::Dyn::with_generation
+/// [`DynMut::with_generation`]: crate::DynMut::with_generation
+/// [`DynPtr`]: crate::DynPtr
+/// [`DynPtr::with_generation`]: crate::DynPtr::with_generation
+#[derive(Debug, Default)]
+pub struct Generation {
+    current: AtomicUsize,
+}
+
+impl Generation {
+    /// Creates a new [`Generation`] starting at generation zero.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            current: AtomicUsize::new(0),
+        }
+    }
+
+    /// Returns the current generation number.
+    #[inline]
+    pub fn current(&self) -> usize {
+        self.current.load(Ordering::Acquire)
+    }
+
+    /// Advances to the next generation, making any [`DynPtr`] values
+    /// associated with an earlier generation stale.
+    ///
+    /// [`DynPtr`]: crate::DynPtr
+    #[inline]
+    pub fn advance(&self) {
+        self.current.fetch_add(1, Ordering::AcqRel);
+    }
+}
+
+/// The [`Generation`] a [`DynPtr`] is associated with, along with the
+/// generation number at the time of association.
+///
+/// [`DynPtr`]: crate::DynPtr
+#[cfg(debug_assertions)]
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct GenerationTag {
+    generation: NonNull<Generation>,
+    seen: usize,
+    /// Set when `generation` is borrowed only for the lifetime of a
+    /// [`Dyn`](crate::Dyn) or [`DynMut`](crate::DynMut), in which case the
+    /// tag must not outlive that handle.
+    borrowed: bool,
+}
+
+#[cfg(debug_assertions)]
+impl GenerationTag {
+    #[inline]
+    pub(crate) fn new(generation: &'static Generation) -> Self {
+        Self {
+            generation: NonNull::from(generation),
+            seen: generation.current(),
+            borrowed: false,
+        }
+    }
+
+    /// # Safety
+    ///
+    /// The result must be discarded before `generation` is moved or dropped.
+    #[inline]
+    pub(crate) unsafe fn new_borrowed(generation: &Generation) -> Self {
+        Self {
+            generation: NonNull::from(generation),
+            seen: generation.current(),
+            borrowed: true,
+        }
+    }
+
+    #[inline]
+    pub(crate) const fn is_borrowed(&self) -> bool {
+        self.borrowed
+    }
+
+    /// Panics if the generation has advanced since this tag was created.
+    #[inline]
+    #[track_caller]
+    pub(crate) fn check(&self) {
+        // Safety: Tags for borrowed generations are discarded before the
+        // borrow ends, and all others refer to a `'static` generation.
+        let generation = unsafe { self.generation.as_ref() };
+        let (seen, current) = (self.seen, generation.current());
+        #[cfg(feature = "minimal-panic")]
+        assert!(current == seen, "DynPtr used after its generation advanced");
+        #[cfg(not(feature = "minimal-panic"))]
+        assert!(
+            current == seen,
+            "DynPtr from generation {seen} used in generation {current}",
+        );
+    }
+}
@@ -0,0 +1,59 @@
+//! Checks the text of the compiler diagnostics that this library customizes,
+//! which `compile_fail` doctests can only check by error code.
+//!
+//! Each case is built as a separate throwaway crate using the same `cargo`
+//! that is running the tests, so these are comparatively slow.
+
+use std::{fs, path::PathBuf, process::Command};
+
+/// Builds a binary crate whose `main.rs` is `source` and which depends on
+/// this library with its default features, returning the compiler's
+/// diagnostics. Panics if the build unexpectedly succeeds.
+fn check_fails(name: &str, source: &str) -> String {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name);
+    fs::create_dir_all(dir.join("src")).unwrap();
+    fs::write(
+        dir.join("Cargo.toml"),
+        format!(
+            "[package]\nname = {name:?}\nversion = \"0.0.0\"\nedition = \"2024\"\n\n\
+             [dependencies]\nany-dyn = {{ path = {:?} }}\n\n[workspace]\n",
+            env!("CARGO_MANIFEST_DIR"),
+        ),
+    )
+    .unwrap();
+    fs::write(dir.join("src/main.rs"), source).unwrap();
+
+    let output = Command::new(env!("CARGO"))
+        .args(["check", "--quiet", "--color", "never", "--manifest-path"])
+        .arg(dir.join("Cargo.toml"))
+        .output()
+        .unwrap();
+    assert!(!output.status.success(), "{name} compiled successfully");
+    String::from_utf8(output.stderr).unwrap()
+}
+
+#[test]
+#[cfg_attr(miri, ignore = "runs the compiler")]
+fn missing_as_trait_object_impl() {
+    let stderr = check_fails(
+        "missing_as_trait_object_impl",
+        r#"
+use any_dyn::traitcast::AsTraitObject;
+struct Forgotten;
+fn main() {
+    let _objs: Vec<Box<dyn AsTraitObject>> = vec![Box::new(Forgotten)];
+}
+"#,
+    );
+    for expected in [
+        "error[E0277]: `Forgotten` does not implement `AsTraitObject`",
+        "`Forgotten` must implement `AsTraitObject` to support trait object casting",
+        "note: implement `AsTraitObject::as_trait_object` using `match_dyn_type_id!` to list \
+         the traits `Forgotten` offers, or use an empty `impl` to offer none",
+    ] {
+        assert!(
+            stderr.contains(expected),
+            "missing {expected:?} in:\n{stderr}"
+        );
+    }
+}
@@ -0,0 +1,67 @@
+//! Demonstrates a type using the `downcast-rs` crate's pattern being cast
+//! through the `traitcast` helpers.
+
+use any_dyn::{
+    Dyn, DynTypeId,
+    traitcast::{
+        AsTraitObject, as_trait_object_via_downcast, cast_then_downcast, cast_trait_object,
+        match_dyn_type_id,
+    },
+};
+use core::any::Any;
+use downcast_rs::{Downcast, impl_downcast};
+
+trait Component: Downcast {
+    fn name(&self) -> &str;
+}
+impl_downcast!(Component);
+
+trait Greeter {
+    fn greet(&self) -> String;
+}
+
+struct Position(i32, i32);
+
+impl Component for Position {
+    fn name(&self) -> &str {
+        "position"
+    }
+}
+
+impl Greeter for Position {
+    fn greet(&self) -> String {
+        format!("hello from ({}, {})", self.0, self.1)
+    }
+}
+
+impl AsTraitObject for Position {
+    fn as_trait_object<'a>(&'a self, type_id: DynTypeId) -> Option<Dyn<'a>> {
+        match_dyn_type_id!(self, type_id => Greeter)
+            .or_else(|| as_trait_object_via_downcast(self, type_id))
+    }
+}
+
+#[test]
+fn concrete_type() {
+    let pos = Position(1, 2);
+    assert_eq!(
+        cast_trait_object::<dyn Greeter>(&pos).unwrap().greet(),
+        "hello from (1, 2)"
+    );
+    let recovered = cast_then_downcast::<Position>(&pos).unwrap();
+    assert_eq!((recovered.0, recovered.1), (1, 2));
+}
+
+#[test]
+fn downcast_trait_object() {
+    let component: Box<dyn Component> = Box::new(Position(3, 4));
+    let erased = as_trait_object_via_downcast(&*component, DynTypeId::of::<dyn Any>()).unwrap();
+    let pos = erased
+        .cast::<dyn Any>()
+        .unwrap()
+        .downcast_ref::<Position>()
+        .unwrap();
+    assert_eq!(component.name(), "position");
+    assert_eq!((pos.0, pos.1), (3, 4));
+    assert!(as_trait_object_via_downcast(&*component, DynTypeId::of::<dyn Greeter>()).is_none());
+}
@@ -0,0 +1,77 @@
+//! Constructs and casts many type-erased trait objects in a loop, without
+//! using `alloc`, to exercise the construction and cast paths at scale.
+
+use any_dyn::{Dyn, DynMut, DynPtr};
+use core::ptr::NonNull;
+
+trait Value {
+    fn value(&self) -> usize;
+}
+
+trait Bump {
+    fn bump(&mut self);
+}
+
+struct Small(u8);
+struct Large([usize; 8]);
+
+impl Value for Small {
+    fn value(&self) -> usize {
+        self.0 as usize
+    }
+}
+
+impl Value for Large {
+    fn value(&self) -> usize {
+        self.0.iter().sum()
+    }
+}
+
+impl Bump for Small {
+    fn bump(&mut self) {
+        self.0 = self.0.wrapping_add(1);
+    }
+}
+
+impl Bump for Large {
+    fn bump(&mut self) {
+        self.0[0] += 1;
+    }
+}
+
+const ITERATIONS: usize = 10_000;
+
+#[test]
+fn many_shared() {
+    let small = Small(3);
+    let large = Large([1; 8]);
+    let objs: [&dyn Value; 2] = [&small, &large];
+
+    let mut total = 0;
+    for i in 0..ITERATIONS {
+        let ptr = DynPtr::new(NonNull::from(objs[i % 2]));
+        assert!(ptr.cast::<dyn Bump>().is_none());
+        let erased = Dyn::new(objs[i % 2]);
+        assert!(erased.as_ptr().ptr_eq(&ptr));
+        total += erased.cast::<dyn Value>().unwrap().value();
+    }
+    assert_eq!(total, (ITERATIONS / 2) * (3 + 8));
+}
+
+#[test]
+fn many_mutable() {
+    let mut small = Small(0);
+    let mut large = Large([0; 8]);
+
+    for i in 0..ITERATIONS {
+        let erased = if i % 2 == 0 {
+            DynMut::new(&mut small as &mut dyn Bump)
+        } else {
+            DynMut::new(&mut large as &mut dyn Bump)
+        };
+        assert!(erased.cast::<dyn Value>().is_none());
+        erased.cast::<dyn Bump>().unwrap().bump();
+    }
+    assert_eq!(small.value(), (ITERATIONS / 2) % 256);
+    assert_eq!(large.value(), ITERATIONS / 2);
+}